@@ -0,0 +1,287 @@
+//! Credential providers used to authenticate requests against Unity Catalog
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+/// Environment variable read by [`EnvToken::default`].
+pub const UC_TOKEN_ENV: &str = "UC_TOKEN";
+
+/// Source of bearer tokens attached to every Unity Catalog request.
+#[async_trait]
+pub trait CredentialProvider: fmt::Debug + Send + Sync {
+    /// Return a bearer token that is valid for the next request.
+    async fn token(&self) -> Result<String, CredentialError>;
+}
+
+/// Static bearer token, e.g. a personal access token (PAT).
+pub struct StaticToken {
+    token: String,
+}
+
+impl StaticToken {
+    /// Create a provider that always returns `token`.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticToken").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StaticToken {
+    async fn token(&self) -> Result<String, CredentialError> {
+        Ok(self.token.clone())
+    }
+}
+
+/// Bearer token read from an environment variable on every request.
+#[derive(Debug)]
+pub struct EnvToken {
+    var: String,
+}
+
+impl EnvToken {
+    /// Create a provider that reads the token from the environment variable `var`.
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl Default for EnvToken {
+    fn default() -> Self {
+        Self::new(UC_TOKEN_ENV)
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for EnvToken {
+    async fn token(&self) -> Result<String, CredentialError> {
+        std::env::var(&self.var)
+            .map_err(|e| CredentialError::new(format!("cannot read ${}: {}", self.var, e)))
+    }
+}
+
+/// OAuth2 client-credentials (machine-to-machine) flow.
+///
+/// Tokens are cached and transparently refreshed once they are within the
+/// refresh margin of their expiry.
+pub struct OAuthClientCredentials {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    refresh_margin: Duration,
+    http: Client,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+impl OAuthClientCredentials {
+    /// Default time before expiry at which a cached token is refreshed.
+    pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+    /// Lifetime assumed for tokens whose response omits `expires_in`.
+    const DEFAULT_LIFETIME: Duration = Duration::from_secs(3600);
+
+    /// Create a provider that exchanges `client_id` and `client_secret` for
+    /// access tokens at `token_url`.
+    pub fn new(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scope: None,
+            refresh_margin: Self::DEFAULT_REFRESH_MARGIN,
+            http: Client::new(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Request the given scope, e.g. `all-apis` for Databricks.
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Refresh cached tokens this long before they expire.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    async fn fetch(&self) -> Result<CachedToken, CredentialError> {
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope));
+        }
+
+        let response = self
+            .http
+            .post(&self.token_url)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| CredentialError::new(format!("token request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CredentialError::new(format!(
+                "token endpoint returned {}: {}",
+                status, body
+            )));
+        }
+
+        let token = response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| CredentialError::new(format!("invalid token response: {}", e)))?;
+        let lifetime = token
+            .expires_in
+            .map(Duration::from_secs)
+            .unwrap_or(Self::DEFAULT_LIFETIME);
+
+        Ok(CachedToken {
+            token: token.access_token,
+            expires_at: Instant::now() + lifetime,
+        })
+    }
+}
+
+impl fmt::Debug for OAuthClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for OAuthClientCredentials {
+    async fn token(&self) -> Result<String, CredentialError> {
+        let mut cached = self.cached.lock().await;
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|c| c.expires_at > Instant::now() + self.refresh_margin);
+        if !fresh {
+            tracing::debug!("refreshing OAuth token from {}", self.token_url);
+            *cached = Some(self.fetch().await?);
+        }
+
+        Ok(cached.as_ref().expect("token cached").token.clone())
+    }
+}
+
+/// Error raised when a [`CredentialProvider`] cannot produce a token.
+#[derive(Debug)]
+pub struct CredentialError {
+    message: String,
+}
+
+impl CredentialError {
+    /// Create a new credential error with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Credential error: {}", self.message)
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
+#[cfg(test)]
+mod test {
+    use wiremock::{
+        matchers::{body_string_contains, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    async fn token_server(expires_in: u64, expected_calls: u64) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oidc/v1/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(header("authorization", "Basic aWQ6c2VjcmV0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "m2m-token",
+                "token_type": "Bearer",
+                "expires_in": expires_in,
+            })))
+            .expect(expected_calls)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_is_cached() {
+        let server = token_server(3600, 1).await;
+        let provider =
+            OAuthClientCredentials::new(format!("{}/oidc/v1/token", server.uri()), "id", "secret");
+
+        assert_eq!(provider.token().await.unwrap(), "m2m-token");
+        assert_eq!(provider.token().await.unwrap(), "m2m-token");
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_is_refreshed_before_expiry() {
+        let server = token_server(30, 2).await;
+        let provider =
+            OAuthClientCredentials::new(format!("{}/oidc/v1/token", server.uri()), "id", "secret");
+
+        // 30s lifetime is within the default 60s refresh margin
+        provider.token().await.unwrap();
+        provider.token().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_endpoint_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("invalid_client"))
+            .mount(&server)
+            .await;
+        let provider = OAuthClientCredentials::new(server.uri(), "id", "secret");
+
+        let err = provider.token().await.unwrap_err();
+        assert!(err.to_string().contains("invalid_client"));
+    }
+}
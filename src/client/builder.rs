@@ -0,0 +1,92 @@
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    auth::CredentialProvider,
+    error::ClientError,
+    rest::{HttpOptions, RestClientError, UnityRestClient},
+    retry::RetryPolicy,
+    UnityClient,
+};
+
+/// Configures and builds a [`UnityClient`] or [`UnityRestClient`].
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{sync::Arc, time::Duration};
+/// use datafusion_unity_catalog::{client::UnityClient, RetryPolicy, StaticToken};
+///
+/// let client = UnityClient::builder("http://localhost:8080/api/2.1/unity-catalog/")
+///     .with_credentials(Arc::new(StaticToken::new("dapi-123")))
+///     .with_retry_policy(RetryPolicy::new().with_max_retries(5))
+///     .with_request_timeout(Some(Duration::from_secs(30)))
+///     .build()?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnityClientBuilder {
+    endpoint: String,
+    pub(crate) credentials: Option<Arc<dyn CredentialProvider>>,
+    pub(crate) http: HttpOptions,
+}
+
+impl UnityClientBuilder {
+    /// Start configuring a client for the Unity Catalog REST API at
+    /// `endpoint`, e.g. `http://localhost:8080/api/2.1/unity-catalog/`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            credentials: None,
+            http: HttpOptions::default(),
+        }
+    }
+
+    /// Connect to `endpoint` instead of the endpoint given to [`new`](Self::new).
+    pub(crate) fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Authenticate every request with the given [`CredentialProvider`].
+    pub fn with_credentials(mut self, credentials: Arc<dyn CredentialProvider>) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Set how requests that fail with a transient error are retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.http.retry_policy = retry_policy;
+        self
+    }
+
+    /// Fail requests that take longer than `timeout`, including reading the
+    /// response. Defaults to 60 seconds.
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.http.request_timeout = timeout;
+        self
+    }
+
+    /// Fail requests that cannot connect to the server within `timeout`.
+    /// Defaults to 10 seconds.
+    pub fn with_connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.http.connect_timeout = timeout;
+        self
+    }
+
+    /// Send requests with `client`, e.g. to configure proxies or TLS. The
+    /// request and connect timeouts are then left to `client`.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http.client = Some(client);
+        self
+    }
+
+    /// Build a [`UnityClient`].
+    pub fn build(self) -> Result<UnityClient, ClientError> {
+        UnityClient::new(&self.endpoint, self.credentials, self.http)
+    }
+
+    /// Build a [`UnityRestClient`], which maps the REST API one to one.
+    pub fn build_rest(self) -> Result<UnityRestClient, RestClientError> {
+        UnityRestClient::new(&self.endpoint, self.credentials, self.http)
+    }
+}
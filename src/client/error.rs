@@ -1,2 +1,222 @@
+//! Errors returned by the Unity Catalog clients
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::rest::RestClientError;
+
+/// Error response returned by the Unity Catalog server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ApiError {
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Unity error code, e.g. `TABLE_DOES_NOT_EXIST`.
+    pub error_code: Option<String>,
+    /// Human readable message from the response body.
+    pub message: Option<String>,
+    /// Structured details of the error, e.g. `google.rpc.ErrorInfo` objects
+    /// with a `reason` and `metadata`.
+    pub details: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error_code: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    details: Vec<Value>,
+}
+
+impl ApiError {
+    pub(crate) fn from_body(status: u16, body: &str) -> ApiError {
+        match serde_json::from_str::<ErrorBody>(body) {
+            Ok(b) => ApiError {
+                status,
+                error_code: b.error_code,
+                message: b.message,
+                details: b.details,
+            },
+            Err(_) => ApiError {
+                status,
+                error_code: None,
+                message: Some(body.to_string()).filter(|b| !b.is_empty()),
+                details: Vec::new(),
+            },
+        }
+    }
+
+    pub(crate) fn has_code(&self, suffixes: &[&str]) -> bool {
+        self.error_code
+            .as_deref()
+            .is_some_and(|c| suffixes.iter().any(|s| c.ends_with(s)))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP {}", self.status)?;
+        if let Some(code) = &self.error_code {
+            write!(f, " {}", code)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error raised by [`UnityClient`](super::UnityClient), naming the object
+/// that could not be accessed.
 #[derive(Debug)]
-pub struct ClientError {}
+#[non_exhaustive]
+pub enum ClientError {
+    /// The catalog, schema or table does not exist.
+    NotFound {
+        /// Full name of the object, e.g. `main.default.sales`.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The caller is not allowed to access the object.
+    PermissionDenied {
+        /// Full name of the object.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The request carried no or invalid credentials.
+    Unauthenticated {
+        /// Description of the failure.
+        message: String,
+    },
+    /// The server rejected the request for another reason.
+    Api {
+        /// Full name of the object.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The server returned a response that could not be interpreted.
+    InvalidResponse {
+        /// Full name of the object.
+        name: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The server could not be reached.
+    Transport {
+        /// Description of the failure.
+        message: String,
+    },
+    /// The client is misconfigured, e.g. with an invalid endpoint.
+    Config {
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+impl ClientError {
+    /// Classify a REST error raised while accessing the object `name`.
+    pub(crate) fn from_rest(name: &str, error: RestClientError) -> ClientError {
+        let name = name.to_string();
+        match error {
+            RestClientError::NotFound(e) => ClientError::NotFound { name, error: e },
+            RestClientError::PermissionDenied(e) => {
+                ClientError::PermissionDenied { name, error: e }
+            }
+            RestClientError::Api(e) if e.status == 401 || e.has_code(&["UNAUTHENTICATED"]) => {
+                ClientError::Unauthenticated {
+                    message: e.to_string(),
+                }
+            }
+            RestClientError::AlreadyExists(e)
+            | RestClientError::InvalidParameter(e)
+            | RestClientError::RateLimited(e)
+            | RestClientError::ServerError(e)
+            | RestClientError::Api(e) => ClientError::Api { name, error: e },
+            RestClientError::Credential(e) => ClientError::Unauthenticated {
+                message: e.to_string(),
+            },
+            RestClientError::Decode(message) => ClientError::InvalidResponse { name, message },
+            RestClientError::InvalidUrl(message) => ClientError::Config { message },
+            RestClientError::Transport(message) => ClientError::Transport { message },
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { name, error } => write!(f, "'{}' not found ({})", name, error),
+            Self::PermissionDenied { name, error } => {
+                write!(f, "Permission denied on '{}' ({})", name, error)
+            }
+            Self::Unauthenticated { message } => write!(f, "Unauthenticated: {}", message),
+            Self::Api { name, error } => write!(f, "Request for '{}' failed ({})", name, error),
+            Self::InvalidResponse { name, message } => {
+                write!(f, "Invalid response for '{}': {}", name, message)
+            }
+            Self::Transport { message } => write!(f, "Transport error: {}", message),
+            Self::Config { message } => write!(f, "Invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_rest_not_found() {
+        let body =
+            r#"{"error_code":"TABLE_DOES_NOT_EXIST","message":"Table not found: main.default.t"}"#;
+        let error = ClientError::from_rest(
+            "main.default.t",
+            RestClientError::from_api(ApiError::from_body(404, body)),
+        );
+
+        let ClientError::NotFound { name, error } = error else {
+            panic!("expected NotFound, got {:?}", error);
+        };
+        assert_eq!(name, "main.default.t");
+        assert_eq!(error.error_code.as_deref(), Some("TABLE_DOES_NOT_EXIST"));
+        assert_eq!(
+            error.to_string(),
+            "HTTP 404 TABLE_DOES_NOT_EXIST: Table not found: main.default.t"
+        );
+    }
+
+    #[test]
+    fn test_from_rest_classification() {
+        let classify = |status, body| {
+            ClientError::from_rest(
+                "main",
+                RestClientError::from_api(ApiError::from_body(status, body)),
+            )
+        };
+
+        assert!(matches!(
+            classify(403, r#"{"error_code":"PERMISSION_DENIED"}"#),
+            ClientError::PermissionDenied { .. }
+        ));
+        assert!(matches!(
+            classify(401, ""),
+            ClientError::Unauthenticated { .. }
+        ));
+        assert!(matches!(
+            classify(500, "<html>oops</html>"),
+            ClientError::Api {
+                error: ApiError {
+                    message: Some(_),
+                    ..
+                },
+                ..
+            }
+        ));
+    }
+}
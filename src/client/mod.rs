@@ -1,21 +1,84 @@
+//! Client for the Unity Catalog REST API
+//!
+//! [`UnityClient`] covers what the DataFusion catalog needs: it follows
+//! pagination and returns validated [`Catalog`], [`Schema`] and [`Table`]
+//! objects. [`UnityRestClient`] maps every endpoint of the open source Unity
+//! Catalog API one to one, including volumes, functions and registered
+//! models. Both are created with a [`UnityClientBuilder`].
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # async {
+//! use datafusion_unity_catalog::client::{rest::types::UpdateSchema, UnityClient};
+//!
+//! let builder = UnityClient::builder("http://localhost:8080/api/2.1/unity-catalog/");
+//! let client = builder.clone().build()?;
+//! for table in client.list_tables("unity", "default").await? {
+//!     println!("{} at {:?}", table.full_name(), table.storage_location());
+//! }
+//!
+//! let rest_client = builder.build_rest()?;
+//! let update = UpdateSchema {
+//!     comment: Some("shared with analytics".to_string()),
+//!     ..Default::default()
+//! };
+//! rest_client.update_schema("unity.default", &update).await?;
+//! # Ok::<(), Box<dyn std::error::Error>>(()) };
+//! # Ok(()) }
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+pub use self::builder::UnityClientBuilder;
 use self::{
+    auth::CredentialProvider,
     error::ClientError,
-    rest::{types::TableInfo, UnityRestClient},
+    rest::{
+        types::{
+            ColumnInfo, CreateCatalog, CreateSchema, CreateTable, DataSourceFormat, TableInfo,
+            TableOperation, TableType, TemporaryCredentials,
+        },
+        HttpOptions, UnityRestClient,
+    },
 };
 
+pub mod auth;
+mod builder;
 pub mod error;
 pub mod rest;
+pub mod retry;
 
+/// Client for the catalogs, schemas and tables registered in Unity Catalog.
+///
+/// Listings follow pagination and errors name the object they concern.
+#[derive(Debug)]
 pub struct UnityClient {
     rest_client: UnityRestClient,
 }
 
 impl UnityClient {
-    pub fn new(endpoint: &str) -> UnityClient {
-        let rest_client = UnityRestClient::new(endpoint);
-        Self { rest_client }
+    /// Start configuring a client for the Unity Catalog REST API at `endpoint`.
+    pub fn builder(endpoint: impl Into<String>) -> UnityClientBuilder {
+        UnityClientBuilder::new(endpoint)
+    }
+
+    pub(crate) fn new(
+        endpoint: &str,
+        credentials: Option<Arc<dyn CredentialProvider>>,
+        options: HttpOptions,
+    ) -> Result<UnityClient, ClientError> {
+        let rest_client = UnityRestClient::new(endpoint, credentials, options)
+            .map_err(|e| ClientError::from_rest(endpoint, e))?;
+        Ok(Self { rest_client })
     }
 
+    /// The underlying client, which covers the whole REST API.
+    pub fn rest_client(&self) -> &UnityRestClient {
+        &self.rest_client
+    }
+
+    /// List all catalogs.
     pub async fn list_catalogs(&self) -> Result<Vec<Catalog>, ClientError> {
         let mut catalogs = Vec::new();
         let mut page_token = None;
@@ -24,19 +87,37 @@ impl UnityClient {
                 .rest_client
                 .list_catalogs(page_token.as_deref(), None)
                 .await
-                .unwrap();
+                .map_err(|e| ClientError::from_rest("catalogs", e))?;
             catalogs.extend(response.catalogs.into_iter().map(|c| c.name));
             page_token = response.next_page_token;
             if page_token.is_none() || page_token.as_ref().is_some_and(|s| s.is_empty()) {
-                let c = catalogs
-                    .into_iter()
-                    .map(|name| Catalog::new(name))
-                    .collect();
+                let c = catalogs.into_iter().map(Catalog::new).collect();
                 break Ok(c);
             }
         }
     }
 
+    /// Create a catalog.
+    pub async fn create_catalog(
+        &self,
+        name: &str,
+        comment: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<Catalog, ClientError> {
+        let request = CreateCatalog {
+            name: name.to_string(),
+            comment: comment.map(str::to_string),
+            properties,
+        };
+        let catalog = self
+            .rest_client
+            .create_catalog(&request)
+            .await
+            .map_err(|e| ClientError::from_rest(name, e))?;
+        Ok(Catalog::new(catalog.name))
+    }
+
+    /// List all schemas of a catalog.
     pub async fn list_schemas(&self, catalog_name: &str) -> Result<Vec<Schema>, ClientError> {
         let mut schemas = Vec::new();
         let mut page_token = None;
@@ -45,7 +126,7 @@ impl UnityClient {
                 .rest_client
                 .list_schemas(catalog_name, page_token.as_deref(), None)
                 .await
-                .unwrap();
+                .map_err(|e| ClientError::from_rest(catalog_name, e))?;
             schemas.extend(response.schemas.into_iter().map(|s| s.name));
             page_token = response.next_page_token;
             if page_token.is_none() || page_token.as_ref().is_some_and(|s| s.is_empty()) {
@@ -58,6 +139,41 @@ impl UnityClient {
         }
     }
 
+    /// Create a schema in a catalog.
+    pub async fn create_schema(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+    ) -> Result<Schema, ClientError> {
+        let request = CreateSchema {
+            name: schema_name.to_string(),
+            catalog_name: catalog_name.to_string(),
+            comment: None,
+            properties: HashMap::new(),
+        };
+        let schema = self
+            .rest_client
+            .create_schema(&request)
+            .await
+            .map_err(|e| ClientError::from_rest(&format!("{}.{}", catalog_name, schema_name), e))?;
+        Ok(Schema::new(catalog_name.to_string(), schema.name))
+    }
+
+    /// Delete a schema. With `force`, the tables in it are deleted as well.
+    pub async fn delete_schema(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        force: bool,
+    ) -> Result<(), ClientError> {
+        let full_name = format!("{}.{}", catalog_name, schema_name);
+        self.rest_client
+            .delete_schema(&full_name, force)
+            .await
+            .map_err(|e| ClientError::from_rest(&full_name, e))
+    }
+
+    /// List all tables and views of a schema.
     pub async fn list_tables(
         &self,
         catalog_name: &str,
@@ -70,109 +186,239 @@ impl UnityClient {
                 .rest_client
                 .list_tables(catalog_name, schema_name, page_token.as_deref(), None)
                 .await
-                .unwrap();
+                .map_err(|e| {
+                    ClientError::from_rest(&format!("{}.{}", catalog_name, schema_name), e)
+                })?;
             tables.extend(response.tables);
             page_token = response.next_page_token;
             if page_token.is_none() || page_token.as_ref().is_some_and(|s| s.is_empty()) {
-                let t = tables
+                break tables
                     .into_iter()
-                    .map(|table_info| {
-                        Table::new(
-                            catalog_name.to_string(),
-                            schema_name.to_string(),
-                            table_info.name.expect("table name"),
-                            table_info.storage_location.expect("storage location"),
-                        )
+                    .map(|mut table_info| {
+                        table_info
+                            .catalog_name
+                            .get_or_insert_with(|| catalog_name.to_string());
+                        table_info
+                            .schema_name
+                            .get_or_insert_with(|| schema_name.to_string());
+                        Table::try_from(table_info)
                     })
                     .collect();
-                break Ok(t);
             }
         }
     }
+
+    /// Fetch a table or view.
+    pub async fn get_table(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Table, ClientError> {
+        let full_name = format!("{}.{}.{}", catalog_name, schema_name, table_name);
+        let table_info = self
+            .rest_client
+            .get_table(&full_name)
+            .await
+            .map_err(|e| ClientError::from_rest(&full_name, e))?;
+        Table::try_from(table_info)
+    }
+
+    /// Register a table.
+    pub async fn create_table(&self, table: &CreateTable) -> Result<Table, ClientError> {
+        let full_name = format!(
+            "{}.{}.{}",
+            table.catalog_name, table.schema_name, table.name
+        );
+        let mut table_info = self
+            .rest_client
+            .create_table(table)
+            .await
+            .map_err(|e| ClientError::from_rest(&full_name, e))?;
+        table_info
+            .catalog_name
+            .get_or_insert_with(|| table.catalog_name.clone());
+        table_info
+            .schema_name
+            .get_or_insert_with(|| table.schema_name.clone());
+        Table::try_from(table_info)
+    }
+
+    /// Remove a table or view from the catalog. The data files are left to
+    /// the server.
+    pub async fn delete_table(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<(), ClientError> {
+        let full_name = format!("{}.{}.{}", catalog_name, schema_name, table_name);
+        self.rest_client
+            .delete_table(&full_name)
+            .await
+            .map_err(|e| ClientError::from_rest(&full_name, e))
+    }
+
+    /// Request short-lived credentials for the storage location of `table`.
+    pub async fn temporary_table_credentials(
+        &self,
+        table: &Table,
+        operation: TableOperation,
+    ) -> Result<TemporaryCredentials, ClientError> {
+        let Some(table_id) = table.table_id() else {
+            return Err(ClientError::InvalidResponse {
+                name: table.full_name(),
+                message: "table is missing 'table_id'".to_string(),
+            });
+        };
+        self.rest_client
+            .generate_temporary_table_credentials(table_id, operation)
+            .await
+            .map_err(|e| ClientError::from_rest(&table.full_name(), e))
+    }
 }
 
+/// A catalog registered in Unity Catalog.
+#[derive(Debug, Clone)]
 pub struct Catalog {
     name: String,
 }
 
 impl Catalog {
-    pub fn new(name: String) -> Catalog {
+    pub(crate) fn new(name: String) -> Catalog {
         Catalog { name }
     }
 
+    /// Name of the catalog.
     pub fn name(&self) -> &str {
         &self.name
     }
 }
 
+/// A schema registered in Unity Catalog.
+#[derive(Debug, Clone)]
 pub struct Schema {
-    _catalog_name: String,
+    catalog_name: String,
     name: String,
 }
 
 impl Schema {
-    pub fn new(catalog_name: String, name: String) -> Schema {
-        Schema {
-            _catalog_name: catalog_name,
-            name,
-        }
+    pub(crate) fn new(catalog_name: String, name: String) -> Schema {
+        Schema { catalog_name, name }
     }
 
-    pub fn _catalog_name(&self) -> &str {
-        &self._catalog_name
+    /// Name of the catalog that contains the schema.
+    pub fn catalog_name(&self) -> &str {
+        &self.catalog_name
     }
 
+    /// Name of the schema.
     pub fn name(&self) -> &str {
         &self.name
     }
 }
 
+/// A table or view registered in Unity Catalog.
+#[derive(Debug, Clone)]
 pub struct Table {
-    _catalog_name: String,
-    _schema_name: String,
+    table_id: Option<String>,
+    catalog_name: String,
+    schema_name: String,
     name: String,
-    storage_location: String,
+    table_type: Option<TableType>,
+    data_source_format: Option<DataSourceFormat>,
+    columns: Vec<ColumnInfo>,
+    storage_location: Option<String>,
+    view_definition: Option<String>,
+    properties: HashMap<String, String>,
 }
 
 impl Table {
-    pub fn new(
-        catalog_name: String,
-        schema_name: String,
-        name: String,
-        storage_location: String,
-    ) -> Table {
-        Table {
-            _catalog_name: catalog_name,
-            _schema_name: schema_name,
-            name,
-            storage_location,
-        }
-    }
-
-    pub fn _catalog_name(&self) -> &str {
-        &self._catalog_name
+    /// Name of the catalog that contains the table.
+    pub fn catalog_name(&self) -> &str {
+        &self.catalog_name
     }
 
-    pub fn _schema_name(&self) -> &str {
-        &self._schema_name
+    /// Name of the schema that contains the table.
+    pub fn schema_name(&self) -> &str {
+        &self.schema_name
     }
 
+    /// Name of the table.
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn storage_location(&self) -> &str {
-        &self.storage_location
+    /// Name of the table qualified with its catalog and schema.
+    pub fn full_name(&self) -> String {
+        format!("{}.{}.{}", self.catalog_name, self.schema_name, self.name)
+    }
+
+    /// Whether this is a managed or external table, or a view.
+    pub fn table_type(&self) -> Option<TableType> {
+        self.table_type
+    }
+
+    /// Unique identifier of the table, used to request credentials.
+    pub fn table_id(&self) -> Option<&str> {
+        self.table_id.as_deref()
+    }
+
+    /// Format of the files at the storage location, absent for views.
+    pub fn data_source_format(&self) -> Option<DataSourceFormat> {
+        self.data_source_format
+    }
+
+    /// Columns as registered in the catalog.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.columns
+    }
+
+    /// Storage location of the table, absent for views.
+    pub fn storage_location(&self) -> Option<&str> {
+        self.storage_location.as_deref()
+    }
+
+    /// SQL text of the view, absent for tables.
+    pub fn view_definition(&self) -> Option<&str> {
+        self.view_definition.as_deref()
+    }
+
+    /// Table properties, e.g. `delta.enableChangeDataFeed`.
+    pub fn properties(&self) -> &HashMap<String, String> {
+        &self.properties
     }
 }
 
-impl From<TableInfo> for Table {
-    fn from(value: TableInfo) -> Self {
-        Table {
-            _catalog_name: value.catalog_name.expect("catalog name"),
-            _schema_name: value.schema_name.expect("schema name"),
-            name: value.name.expect("table name"),
-            storage_location: value.storage_location.expect("storage location"),
-        }
+impl TryFrom<TableInfo> for Table {
+    type Error = ClientError;
+
+    fn try_from(value: TableInfo) -> Result<Self, Self::Error> {
+        let invalid = |field: &str| ClientError::InvalidResponse {
+            name: value.name.clone().unwrap_or_default(),
+            message: format!("table is missing '{}'", field),
+        };
+        let catalog_name = value
+            .catalog_name
+            .clone()
+            .ok_or_else(|| invalid("catalog_name"))?;
+        let schema_name = value
+            .schema_name
+            .clone()
+            .ok_or_else(|| invalid("schema_name"))?;
+        let name = value.name.clone().ok_or_else(|| invalid("name"))?;
+
+        Ok(Table {
+            table_id: value.table_id,
+            catalog_name,
+            schema_name,
+            name,
+            table_type: value.table_type,
+            data_source_format: value.data_source_format,
+            columns: value.columns,
+            storage_location: value.storage_location,
+            view_definition: value.view_definition,
+            properties: value.properties,
+        })
     }
 }
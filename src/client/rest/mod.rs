@@ -1,26 +1,189 @@
-use std::fmt;
+//! Client that maps the Unity Catalog REST API one to one
 
-use reqwest::Client;
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Serialize};
 use url::Url;
 
-use self::types::{ListCatalogsResponse, ListSchemasResponse, ListTablesResponse};
+use self::types::{
+    CatalogInfo, CreateCatalog, CreateFunction, CreateFunctionRequest, CreateRegisteredModel,
+    CreateSchema, CreateTable, CreateVolume, FunctionInfo, GenerateTemporaryTableCredential,
+    ListCatalogsResponse, ListFunctionsResponse, ListRegisteredModelsResponse, ListSchemasResponse,
+    ListTablesResponse, ListVolumesResponse, RegisteredModelInfo, SchemaInfo, TableInfo,
+    TableOperation, TemporaryCredentials, UpdateCatalog, UpdateRegisteredModel, UpdateSchema,
+    UpdateVolume, VolumeInfo,
+};
+use super::{
+    auth::{CredentialError, CredentialProvider},
+    builder::UnityClientBuilder,
+    error::ApiError,
+    retry::{is_retryable, RetryPolicy},
+};
 
 pub mod types;
 
+/// Timeouts and retries of the requests sent to the server.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpOptions {
+    /// How requests that fail with a transient error are retried.
+    pub retry_policy: RetryPolicy,
+    /// Time allowed for a request, from connecting until the response body
+    /// has been read.
+    pub request_timeout: Option<Duration>,
+    /// Time allowed for connecting to the server.
+    pub connect_timeout: Option<Duration>,
+    /// Client used to send requests instead of one built with the timeouts.
+    pub client: Option<Client>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            retry_policy: RetryPolicy::default(),
+            request_timeout: Some(Duration::from_secs(60)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            client: None,
+        }
+    }
+}
+
+/// Client that maps the endpoints of the Unity Catalog REST API one to one.
+///
+/// List endpoints return a single page; pass the `next_page_token` of a
+/// response to fetch the next one. Objects are addressed by their full
+/// name, e.g. `catalog.schema.table`.
+#[derive(Debug)]
 pub struct UnityRestClient {
     base: Url,
     client: Client,
+    credentials: Option<Arc<dyn CredentialProvider>>,
+    retry_policy: RetryPolicy,
 }
 
 impl UnityRestClient {
-    pub fn new(base_url: &str) -> UnityRestClient {
-        let client = Client::new();
-        Self {
-            base: Url::parse(base_url).expect("valid"),
+    /// Start configuring a client for the Unity Catalog REST API at `base_url`.
+    pub fn builder(base_url: impl Into<String>) -> UnityClientBuilder {
+        UnityClientBuilder::new(base_url)
+    }
+
+    pub(crate) fn new(
+        base_url: &str,
+        credentials: Option<Arc<dyn CredentialProvider>>,
+        options: HttpOptions,
+    ) -> Result<UnityRestClient, RestClientError> {
+        let base = Url::parse(base_url)
+            .map_err(|e| RestClientError::InvalidUrl(format!("{}: {}", base_url, e)))?;
+        if base.cannot_be_a_base() {
+            return Err(RestClientError::InvalidUrl(base_url.to_string()));
+        }
+
+        let client = match options.client {
+            Some(client) => client,
+            None => {
+                let mut client = Client::builder();
+                if let Some(timeout) = options.request_timeout {
+                    client = client.timeout(timeout);
+                }
+                if let Some(timeout) = options.connect_timeout {
+                    client = client.connect_timeout(timeout);
+                }
+                client.build()?
+            }
+        };
+        Ok(Self {
+            base,
             client,
+            credentials,
+            retry_policy: options.retry_policy,
+        })
+    }
+
+    async fn get(&self, url: Url) -> Result<RequestBuilder, RestClientError> {
+        self.request(Method::GET, url).await
+    }
+
+    async fn post<B: Serialize>(
+        &self,
+        url: Url,
+        body: &B,
+    ) -> Result<RequestBuilder, RestClientError> {
+        Ok(self.request(Method::POST, url).await?.json(body))
+    }
+
+    async fn patch<B: Serialize>(
+        &self,
+        url: Url,
+        body: &B,
+    ) -> Result<RequestBuilder, RestClientError> {
+        Ok(self.request(Method::PATCH, url).await?.json(body))
+    }
+
+    async fn delete(&self, url: Url) -> Result<RequestBuilder, RestClientError> {
+        self.request(Method::DELETE, url).await
+    }
+
+    async fn request(&self, method: Method, url: Url) -> Result<RequestBuilder, RestClientError> {
+        let request = self.client.request(method, url);
+        match &self.credentials {
+            Some(credentials) => Ok(request.bearer_auth(credentials.token().await?)),
+            None => Ok(request),
+        }
+    }
+
+    /// Send `request`, retrying transient failures according to the retry
+    /// policy, and fail unless the final response is successful.
+    async fn respond(&self, request: RequestBuilder) -> Result<Response, RestClientError> {
+        // requests with a streaming body cannot be sent twice
+        let Some(idempotent) = request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .map(|request| request.method().is_idempotent())
+        else {
+            return check_status(request.send().await?).await;
+        };
+
+        let mut retry = 0;
+        loop {
+            let attempt = request.try_clone().expect("request was cloned before");
+            let (backoff, reason) = match attempt.send().await {
+                Ok(response) if idempotent && is_retryable(response.status()) => {
+                    let Some(backoff) = self.retry_policy.backoff(retry, Some(response.headers()))
+                    else {
+                        return check_status(response).await;
+                    };
+                    (backoff, response.status().to_string())
+                }
+                Ok(response) => return check_status(response).await,
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    let Some(backoff) = self.retry_policy.backoff(retry, None) else {
+                        return Err(e.into());
+                    };
+                    (backoff, e.to_string())
+                }
+                Err(e) => return Err(e.into()),
+            };
+            tracing::debug!("retrying request in {:?} after {}", backoff, reason);
+            tokio::time::sleep(backoff).await;
+            retry += 1;
         }
     }
 
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, RestClientError> {
+        let body = self.respond(request).await?.bytes().await?;
+        serde_json::from_slice(&body).map_err(|e| RestClientError::Decode(e.to_string()))
+    }
+
+    /// Send a request whose response body carries no information.
+    async fn execute(&self, request: RequestBuilder) -> Result<(), RestClientError> {
+        self.respond(request).await?;
+        Ok(())
+    }
+
+    /// List a page of catalogs.
     pub async fn list_catalogs(
         &self,
         page_token: Option<&str>,
@@ -31,15 +194,53 @@ impl UnityRestClient {
         add_query_param(&mut url, "page_token", page_token);
         add_query_param(&mut url, "max_results", max_results);
 
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(From::from)
+        self.send(self.get(url).await?).await
+    }
+
+    /// Create a catalog.
+    pub async fn create_catalog(
+        &self,
+        catalog: &CreateCatalog,
+    ) -> Result<CatalogInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "catalogs");
+
+        self.send(self.post(url, catalog).await?).await
+    }
+
+    /// Fetch a catalog.
+    pub async fn get_catalog(&self, name: &str) -> Result<CatalogInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "catalogs");
+        add_path_segment(&mut url, name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Rename a catalog or change its comment or properties.
+    pub async fn update_catalog(
+        &self,
+        name: &str,
+        update: &UpdateCatalog,
+    ) -> Result<CatalogInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "catalogs");
+        add_path_segment(&mut url, name);
+
+        self.send(self.patch(url, update).await?).await
+    }
+
+    /// Delete a catalog. With `force`, the schemas in it are deleted as well.
+    pub async fn delete_catalog(&self, name: &str, force: bool) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "catalogs");
+        add_path_segment(&mut url, name);
+        add_query_param(&mut url, "force", force.then_some("true"));
+
+        self.execute(self.delete(url).await?).await
     }
 
+    /// List a page of the schemas of a catalog.
     pub async fn list_schemas(
         &self,
         catalog_name: &str,
@@ -52,15 +253,53 @@ impl UnityRestClient {
         add_query_param(&mut url, "page_token", page_token);
         add_query_param(&mut url, "max_results", max_results);
 
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .json::<ListSchemasResponse>()
-            .await
-            .map_err(From::from)
+        self.send(self.get(url).await?).await
+    }
+
+    /// Create a schema.
+    pub async fn create_schema(
+        &self,
+        schema: &CreateSchema,
+    ) -> Result<SchemaInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "schemas");
+
+        self.send(self.post(url, schema).await?).await
     }
 
+    /// Fetch a schema.
+    pub async fn get_schema(&self, full_name: &str) -> Result<SchemaInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "schemas");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Rename a schema or change its comment or properties.
+    pub async fn update_schema(
+        &self,
+        full_name: &str,
+        update: &UpdateSchema,
+    ) -> Result<SchemaInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "schemas");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.patch(url, update).await?).await
+    }
+
+    /// Delete a schema. With `force`, the tables in it are deleted as well.
+    pub async fn delete_schema(&self, full_name: &str, force: bool) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "schemas");
+        add_path_segment(&mut url, full_name);
+        add_query_param(&mut url, "force", force.then_some("true"));
+
+        self.execute(self.delete(url).await?).await
+    }
+
+    /// List a page of the tables of a schema.
     pub async fn list_tables(
         &self,
         catalog_name: &str,
@@ -75,19 +314,245 @@ impl UnityRestClient {
         add_query_param(&mut url, "page_token", page_token);
         add_query_param(&mut url, "max_results", max_results);
 
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .json::<ListTablesResponse>()
-            .await
-            .map_err(From::from)
+        self.send(self.get(url).await?).await
+    }
+
+    /// Fetch a table.
+    pub async fn get_table(&self, full_name: &str) -> Result<TableInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "tables");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Register a table.
+    pub async fn create_table(&self, table: &CreateTable) -> Result<TableInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "tables");
+
+        self.send(self.post(url, table).await?).await
+    }
+
+    /// Remove a table from the catalog.
+    pub async fn delete_table(&self, full_name: &str) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "tables");
+        add_path_segment(&mut url, full_name);
+
+        self.execute(self.delete(url).await?).await
+    }
+
+    /// List a page of the volumes of a schema.
+    pub async fn list_volumes(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        page_token: Option<&str>,
+        max_results: Option<&str>,
+    ) -> Result<ListVolumesResponse, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "volumes");
+        add_query_param(&mut url, "catalog_name", Some(catalog_name));
+        add_query_param(&mut url, "schema_name", Some(schema_name));
+        add_query_param(&mut url, "page_token", page_token);
+        add_query_param(&mut url, "max_results", max_results);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Fetch a volume.
+    pub async fn get_volume(&self, full_name: &str) -> Result<VolumeInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "volumes");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Create a volume.
+    pub async fn create_volume(
+        &self,
+        volume: &CreateVolume,
+    ) -> Result<VolumeInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "volumes");
+
+        self.send(self.post(url, volume).await?).await
+    }
+
+    /// Rename a volume or change its comment.
+    pub async fn update_volume(
+        &self,
+        full_name: &str,
+        update: &UpdateVolume,
+    ) -> Result<VolumeInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "volumes");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.patch(url, update).await?).await
+    }
+
+    /// Delete a volume.
+    pub async fn delete_volume(&self, full_name: &str) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "volumes");
+        add_path_segment(&mut url, full_name);
+
+        self.execute(self.delete(url).await?).await
+    }
+
+    /// List a page of the functions of a schema.
+    pub async fn list_functions(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        page_token: Option<&str>,
+        max_results: Option<&str>,
+    ) -> Result<ListFunctionsResponse, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "functions");
+        add_query_param(&mut url, "catalog_name", Some(catalog_name));
+        add_query_param(&mut url, "schema_name", Some(schema_name));
+        add_query_param(&mut url, "page_token", page_token);
+        add_query_param(&mut url, "max_results", max_results);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Fetch a function.
+    pub async fn get_function(&self, full_name: &str) -> Result<FunctionInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "functions");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Register a function.
+    pub async fn create_function(
+        &self,
+        function: &CreateFunction,
+    ) -> Result<FunctionInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "functions");
+        let body = CreateFunctionRequest {
+            function_info: function,
+        };
+
+        self.send(self.post(url, &body).await?).await
+    }
+
+    /// Delete a function.
+    pub async fn delete_function(&self, full_name: &str) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "functions");
+        add_path_segment(&mut url, full_name);
+
+        self.execute(self.delete(url).await?).await
+    }
+
+    /// List a page of the registered models of a schema.
+    pub async fn list_registered_models(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        page_token: Option<&str>,
+        max_results: Option<&str>,
+    ) -> Result<ListRegisteredModelsResponse, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "models");
+        add_query_param(&mut url, "catalog_name", Some(catalog_name));
+        add_query_param(&mut url, "schema_name", Some(schema_name));
+        add_query_param(&mut url, "page_token", page_token);
+        add_query_param(&mut url, "max_results", max_results);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Fetch a registered model.
+    pub async fn get_registered_model(
+        &self,
+        full_name: &str,
+    ) -> Result<RegisteredModelInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "models");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.get(url).await?).await
+    }
+
+    /// Register a model.
+    pub async fn create_registered_model(
+        &self,
+        model: &CreateRegisteredModel,
+    ) -> Result<RegisteredModelInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "models");
+
+        self.send(self.post(url, model).await?).await
+    }
+
+    /// Rename a registered model or change its comment.
+    pub async fn update_registered_model(
+        &self,
+        full_name: &str,
+        update: &UpdateRegisteredModel,
+    ) -> Result<RegisteredModelInfo, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "models");
+        add_path_segment(&mut url, full_name);
+
+        self.send(self.patch(url, update).await?).await
+    }
+
+    /// Delete a registered model. With `force`, its versions are deleted as well.
+    pub async fn delete_registered_model(
+        &self,
+        full_name: &str,
+        force: bool,
+    ) -> Result<(), RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "models");
+        add_path_segment(&mut url, full_name);
+        add_query_param(&mut url, "force", force.then_some("true"));
+
+        self.execute(self.delete(url).await?).await
+    }
+
+    /// Request short-lived credentials for the storage location of a table.
+    pub async fn generate_temporary_table_credentials(
+        &self,
+        table_id: &str,
+        operation: TableOperation,
+    ) -> Result<TemporaryCredentials, RestClientError> {
+        let mut url = self.base.clone();
+        add_path_segment(&mut url, "temporary-table-credentials");
+        let body = GenerateTemporaryTableCredential {
+            table_id: table_id.to_string(),
+            operation,
+        };
+
+        self.send(self.post(url, &body).await?).await
     }
 }
 
+async fn check_status(response: Response) -> Result<Response, RestClientError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(RestClientError::from_api(ApiError::from_body(
+            status.as_u16(),
+            &body,
+        )));
+    }
+    Ok(response)
+}
+
 fn add_path_segment(url: &mut Url, segment: &str) {
     let mut path = url.path_segments_mut().expect("valid base");
-    path.push(segment);
+    path.pop_if_empty().push(segment);
 }
 
 fn add_query_param<T: AsRef<str>>(url: &mut Url, key: &str, value: Option<T>) {
@@ -96,15 +561,78 @@ fn add_query_param<T: AsRef<str>>(url: &mut Url, key: &str, value: Option<T>) {
     }
 }
 
+/// Error raised by [`UnityRestClient`].
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum RestClientError {
-    Unknown(String),
+    /// The object does not exist.
+    NotFound(ApiError),
+    /// An object with the same name exists already.
+    AlreadyExists(ApiError),
+    /// The caller is not allowed to perform the request.
+    PermissionDenied(ApiError),
+    /// The request is malformed or names an invalid value.
+    InvalidParameter(ApiError),
+    /// Too many requests, even after retrying.
+    RateLimited(ApiError),
+    /// The server failed, even after retrying.
+    ServerError(ApiError),
+    /// The server rejected the request for another reason, e.g. because it
+    /// is not authenticated.
+    Api(ApiError),
+    /// No token could be obtained for the request.
+    Credential(CredentialError),
+    /// The response body could not be decoded.
+    Decode(String),
+    /// The endpoint is not a valid base URL.
+    InvalidUrl(String),
+    /// The server could not be reached or did not respond in time.
+    Transport(String),
+}
+
+impl RestClientError {
+    /// Classify an error response by its Unity error code, or by its status
+    /// if the code is missing or unknown.
+    pub(crate) fn from_api(error: ApiError) -> RestClientError {
+        let code = |suffixes: &[&str]| error.has_code(suffixes);
+        if code(&["NOT_FOUND", "NOT_EXIST"]) {
+            Self::NotFound(error)
+        } else if code(&["ALREADY_EXISTS"]) {
+            Self::AlreadyExists(error)
+        } else if code(&["PERMISSION_DENIED"]) {
+            Self::PermissionDenied(error)
+        } else if code(&["INVALID_PARAMETER_VALUE", "INVALID_ARGUMENT", "BAD_REQUEST"]) {
+            Self::InvalidParameter(error)
+        } else if code(&["RESOURCE_EXHAUSTED", "REQUEST_LIMIT_EXCEEDED"]) {
+            Self::RateLimited(error)
+        } else {
+            match error.status {
+                404 => Self::NotFound(error),
+                409 => Self::AlreadyExists(error),
+                403 => Self::PermissionDenied(error),
+                400 => Self::InvalidParameter(error),
+                429 => Self::RateLimited(error),
+                500..=599 => Self::ServerError(error),
+                _ => Self::Api(error),
+            }
+        }
+    }
 }
 
 impl fmt::Display for RestClientError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Unknown(e) => write!(f, "Unknown error: {}", e),
+            Self::NotFound(e)
+            | Self::AlreadyExists(e)
+            | Self::PermissionDenied(e)
+            | Self::InvalidParameter(e)
+            | Self::RateLimited(e)
+            | Self::ServerError(e)
+            | Self::Api(e) => write!(f, "{}", e),
+            Self::Credential(e) => write!(f, "{}", e),
+            Self::Decode(e) => write!(f, "Invalid response body: {}", e),
+            Self::InvalidUrl(e) => write!(f, "Invalid endpoint URL: {}", e),
+            Self::Transport(e) => write!(f, "Transport error: {}", e),
         }
     }
 }
@@ -114,6 +642,561 @@ impl std::error::Error for RestClientError {}
 impl From<reqwest::Error> for RestClientError {
     fn from(e: reqwest::Error) -> Self {
         tracing::error!("REQWEST ERROR: {:?}", e);
-        Self::Unknown(e.to_string())
+        Self::Transport(e.to_string())
+    }
+}
+
+impl From<CredentialError> for RestClientError {
+    fn from(e: CredentialError) -> Self {
+        Self::Credential(e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, body_partial_json, header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::{types::*, *};
+    use crate::client::auth::StaticToken;
+
+    fn retrying_client(server: &MockServer, request_timeout: Option<Duration>) -> UnityRestClient {
+        let options = HttpOptions {
+            retry_policy: RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_backoff(Duration::from_millis(10))
+                .with_max_backoff(Duration::from_secs(2)),
+            request_timeout,
+            ..Default::default()
+        };
+        UnityRestClient::new(&server.uri(), None, options).unwrap()
+    }
+
+    async fn mock_failures(
+        server: &MockServer,
+        method_name: &str,
+        failure: ResponseTemplate,
+        n: u64,
+    ) {
+        Mock::given(method(method_name))
+            .respond_with(failure)
+            .up_to_n_times(n)
+            .expect(n)
+            .mount(server)
+            .await;
+    }
+
+    async fn mock_catalogs(server: &MockServer, expected_calls: u64) {
+        Mock::given(method("GET"))
+            .and(path("/catalogs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "catalogs": [{"id": "1", "name": "unity"}],
+            })))
+            .expect(expected_calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_is_attached() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/catalogs"))
+            .and(header("authorization", "Bearer dapi-123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "catalogs": [{"id": "1", "name": "unity"}],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnityRestClient::new(
+            &server.uri(),
+            Some(Arc::new(StaticToken::new("dapi-123"))),
+            HttpOptions::default(),
+        )
+        .unwrap();
+        let response = client.list_catalogs(None, None).await.unwrap();
+        assert_eq!(response.catalogs[0].name, "unity");
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let server = MockServer::start().await;
+        mock_failures(&server, "GET", ResponseTemplate::new(503), 1).await;
+        mock_failures(&server, "GET", ResponseTemplate::new(429), 1).await;
+        mock_catalogs(&server, 1).await;
+
+        let client = retrying_client(&server, None);
+        let response = client.list_catalogs(None, None).await.unwrap();
+        assert_eq!(response.catalogs[0].name, "unity");
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_limited() {
+        let server = MockServer::start().await;
+        mock_failures(&server, "GET", ResponseTemplate::new(502), 3).await;
+
+        let client = retrying_client(&server, None);
+        let error = client.list_catalogs(None, None).await.unwrap_err();
+        assert!(matches!(
+            error,
+            RestClientError::ServerError(ApiError { status: 502, .. })
+        ));
+
+        // client errors are not transient
+        let server = MockServer::start().await;
+        mock_failures(&server, "GET", ResponseTemplate::new(400), 1).await;
+        let client = retrying_client(&server, None);
+        client.list_catalogs(None, None).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_requests_are_not_retried() {
+        let server = MockServer::start().await;
+        mock_failures(&server, "POST", ResponseTemplate::new(503), 1).await;
+
+        let client = retrying_client(&server, None);
+        let catalog = CreateCatalog {
+            name: "unity".to_string(),
+            comment: None,
+            properties: Default::default(),
+        };
+        let error = client.create_catalog(&catalog).await.unwrap_err();
+        assert!(matches!(
+            error,
+            RestClientError::ServerError(ApiError { status: 503, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_honored() {
+        let server = MockServer::start().await;
+        let throttled = ResponseTemplate::new(429).insert_header("retry-after", "1");
+        mock_failures(&server, "GET", throttled, 1).await;
+        mock_catalogs(&server, 1).await;
+
+        let client = retrying_client(&server, None);
+        let start = Instant::now();
+        client.list_catalogs(None, None).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        // waiting longer than the maximum backoff fails the request
+        let server = MockServer::start().await;
+        let throttled = ResponseTemplate::new(429).insert_header("retry-after", "60");
+        mock_failures(&server, "GET", throttled, 1).await;
+        let client = retrying_client(&server, None);
+        let error = client.list_catalogs(None, None).await.unwrap_err();
+        assert!(matches!(
+            error,
+            RestClientError::RateLimited(ApiError { status: 429, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_responses_time_out() {
+        let server = MockServer::start().await;
+        let slow = ResponseTemplate::new(200).set_delay(Duration::from_millis(500));
+        mock_failures(&server, "GET", slow, 1).await;
+        mock_catalogs(&server, 1).await;
+
+        let client = retrying_client(&server, Some(Duration::from_millis(100)));
+        client.list_catalogs(None, None).await.unwrap();
+
+        let server = MockServer::start().await;
+        let slow = ResponseTemplate::new(200).set_delay(Duration::from_millis(500));
+        mock_failures(&server, "GET", slow, 3).await;
+        let client = retrying_client(&server, Some(Duration::from_millis(100)));
+        let error = client.list_catalogs(None, None).await.unwrap_err();
+        assert!(matches!(error, RestClientError::Transport(_)));
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_are_classified() {
+        let server = MockServer::start().await;
+        let error = |status, code: &str| {
+            ResponseTemplate::new(status).set_body_json(serde_json::json!({
+                "error_code": code,
+                "message": "request failed",
+                "details": [{
+                    "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+                    "reason": code,
+                    "metadata": {},
+                }],
+            }))
+        };
+        for (name, response) in [
+            ("missing", error(404, "TABLE_DOES_NOT_EXIST")),
+            ("exists", error(409, "TABLE_ALREADY_EXISTS")),
+            ("denied", error(403, "PERMISSION_DENIED")),
+            ("invalid", error(400, "INVALID_PARAMETER_VALUE")),
+            ("throttled", error(429, "REQUEST_LIMIT_EXCEEDED")),
+            ("failing", error(500, "INTERNAL_ERROR")),
+            ("unauthenticated", error(401, "UNAUTHENTICATED")),
+            (
+                "html",
+                ResponseTemplate::new(404).set_body_string("<html>gone</html>"),
+            ),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/tables/{}", name)))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+        }
+
+        let client = UnityRestClient::new(
+            &server.uri(),
+            None,
+            HttpOptions {
+                retry_policy: RetryPolicy::never(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let get = |name| client.get_table(name);
+
+        let RestClientError::NotFound(error) = get("missing").await.unwrap_err() else {
+            panic!("expected NotFound");
+        };
+        assert_eq!(error.error_code.as_deref(), Some("TABLE_DOES_NOT_EXIST"));
+        assert_eq!(error.message.as_deref(), Some("request failed"));
+        assert_eq!(error.details[0]["reason"], "TABLE_DOES_NOT_EXIST");
+        assert!(matches!(
+            get("exists").await.unwrap_err(),
+            RestClientError::AlreadyExists(_)
+        ));
+        assert!(matches!(
+            get("denied").await.unwrap_err(),
+            RestClientError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            get("invalid").await.unwrap_err(),
+            RestClientError::InvalidParameter(_)
+        ));
+        assert!(matches!(
+            get("throttled").await.unwrap_err(),
+            RestClientError::RateLimited(_)
+        ));
+        assert!(matches!(
+            get("failing").await.unwrap_err(),
+            RestClientError::ServerError(_)
+        ));
+        assert!(matches!(
+            get("unauthenticated").await.unwrap_err(),
+            RestClientError::Api(ApiError { status: 401, .. })
+        ));
+        let RestClientError::NotFound(error) = get("html").await.unwrap_err() else {
+            panic!("expected NotFound");
+        };
+        assert_eq!(error.message.as_deref(), Some("<html>gone</html>"));
+    }
+
+    async fn mock(server: &MockServer, mock: wiremock::MockBuilder, response: serde_json::Value) {
+        mock.respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_catalog_and_schema_endpoints() {
+        let server = MockServer::start().await;
+        let catalog = json!({ "id": "1", "name": "unity", "owner": "admin" });
+        let schema = json!({ "schema_id": "2", "name": "default", "catalog_name": "unity" });
+        mock(
+            &server,
+            Mock::given(method("GET")).and(path("/catalogs/unity")),
+            catalog,
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("PATCH"))
+                .and(path("/catalogs/unity"))
+                .and(body_json(json!({ "new_name": "main", "properties": {} }))),
+            json!({ "id": "1", "name": "main" }),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("DELETE"))
+                .and(path("/catalogs/main"))
+                .and(query_param("force", "true")),
+            json!({}),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("GET")).and(path("/schemas/unity.default")),
+            schema,
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("PATCH"))
+                .and(path("/schemas/unity.default"))
+                .and(body_json(json!({ "comment": "shared" }))),
+            json!({ "schema_id": "2", "name": "default", "comment": "shared" }),
+        )
+        .await;
+
+        let client = UnityRestClient::new(&server.uri(), None, HttpOptions::default()).unwrap();
+        let catalog = client.get_catalog("unity").await.unwrap();
+        assert_eq!(catalog.owner.as_deref(), Some("admin"));
+        let update = UpdateCatalog {
+            new_name: Some("main".to_string()),
+            properties: Some(Default::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            client.update_catalog("unity", &update).await.unwrap().name,
+            "main"
+        );
+        client.delete_catalog("main", true).await.unwrap();
+
+        let schema = client.get_schema("unity.default").await.unwrap();
+        assert_eq!(schema.catalog_name.as_deref(), Some("unity"));
+        let update = UpdateSchema {
+            comment: Some("shared".to_string()),
+            ..Default::default()
+        };
+        let schema = client
+            .update_schema("unity.default", &update)
+            .await
+            .unwrap();
+        assert_eq!(schema.comment.as_deref(), Some("shared"));
+    }
+
+    #[tokio::test]
+    async fn test_volume_function_and_model_endpoints() {
+        let server = MockServer::start().await;
+        let volume = json!({
+            "volume_id": "3",
+            "name": "files",
+            "catalog_name": "unity",
+            "schema_name": "default",
+            "volume_type": "EXTERNAL",
+            "storage_location": "s3://bucket/files",
+        });
+        let function = json!({
+            "name": "add_one",
+            "catalog_name": "unity",
+            "schema_name": "default",
+            "data_type": "INT",
+            "routine_body": "SQL",
+            "routine_definition": "x + 1",
+            "input_params": { "parameters": [
+                { "name": "x", "type_text": "int", "type_json": "{}", "type_name": "INT", "position": 0 },
+            ]},
+        });
+        let model = json!({ "id": "4", "name": "churn", "full_name": "unity.default.churn" });
+        mock(
+            &server,
+            Mock::given(method("POST"))
+                .and(path("/volumes"))
+                .and(body_partial_json(
+                    json!({ "name": "files", "volume_type": "EXTERNAL" }),
+                )),
+            volume.clone(),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("GET"))
+                .and(path("/volumes"))
+                .and(query_param("schema_name", "default")),
+            json!({ "volumes": [volume] }),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("PATCH"))
+                .and(path("/volumes/unity.default.files"))
+                .and(body_json(json!({ "new_name": "data" }))),
+            json!({ "name": "data" }),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("DELETE")).and(path("/volumes/unity.default.data")),
+            json!({}),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("POST"))
+                .and(path("/functions"))
+                .and(body_partial_json(json!({ "function_info": {
+                    "name": "add_one",
+                    "routine_body": "SQL",
+                    "parameter_style": "S",
+                    "sql_data_access": "CONTAINS_SQL",
+                }}))),
+            function.clone(),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("GET")).and(path("/functions/unity.default.add_one")),
+            function,
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("DELETE")).and(path("/functions/unity.default.add_one")),
+            json!({}),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("POST"))
+                .and(path("/models"))
+                .and(body_json(
+                    json!({ "name": "churn", "catalog_name": "unity", "schema_name": "default" }),
+                )),
+            model.clone(),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("GET")).and(path("/models")),
+            json!({ "registered_models": [model] }),
+        )
+        .await;
+        mock(
+            &server,
+            Mock::given(method("DELETE"))
+                .and(path("/models/unity.default.churn"))
+                .and(query_param("force", "true")),
+            json!({}),
+        )
+        .await;
+
+        let client = UnityRestClient::new(&server.uri(), None, HttpOptions::default()).unwrap();
+        let volume = CreateVolume {
+            name: "files".to_string(),
+            catalog_name: "unity".to_string(),
+            schema_name: "default".to_string(),
+            volume_type: VolumeType::External,
+            storage_location: Some("s3://bucket/files".to_string()),
+            comment: None,
+        };
+        let volume = client.create_volume(&volume).await.unwrap();
+        assert_eq!(volume.volume_type, Some(VolumeType::External));
+        let volumes = client
+            .list_volumes("unity", "default", None, None)
+            .await
+            .unwrap();
+        assert_eq!(volumes.volumes.len(), 1);
+        let update = UpdateVolume {
+            new_name: Some("data".to_string()),
+            ..Default::default()
+        };
+        client
+            .update_volume("unity.default.files", &update)
+            .await
+            .unwrap();
+        client.delete_volume("unity.default.data").await.unwrap();
+
+        let parameter = FunctionParameterInfo {
+            name: "x".to_string(),
+            type_text: "int".to_string(),
+            type_json: "{}".to_string(),
+            type_name: ColumnTypeName::Int,
+            type_precision: None,
+            type_scale: None,
+            type_interval_type: None,
+            position: 0,
+            parameter_mode: None,
+            parameter_type: None,
+            parameter_default: None,
+            comment: None,
+        };
+        let function = CreateFunction {
+            name: "add_one".to_string(),
+            catalog_name: "unity".to_string(),
+            schema_name: "default".to_string(),
+            input_params: FunctionParameterInfos {
+                parameters: vec![parameter],
+            },
+            data_type: ColumnTypeName::Int,
+            full_data_type: "int".to_string(),
+            return_params: None,
+            routine_body: RoutineBody::Sql,
+            routine_definition: "x + 1".to_string(),
+            parameter_style: ParameterStyle::S,
+            is_deterministic: true,
+            sql_data_access: SqlDataAccess::ContainsSql,
+            is_null_call: false,
+            security_type: SecurityType::Definer,
+            specific_name: "add_one".to_string(),
+            external_language: None,
+            comment: None,
+            properties: None,
+        };
+        client.create_function(&function).await.unwrap();
+        let function = client.get_function("unity.default.add_one").await.unwrap();
+        assert_eq!(function.input_params.unwrap().parameters[0].name, "x");
+        client
+            .delete_function("unity.default.add_one")
+            .await
+            .unwrap();
+
+        let model = CreateRegisteredModel {
+            name: "churn".to_string(),
+            catalog_name: "unity".to_string(),
+            schema_name: "default".to_string(),
+            comment: None,
+        };
+        client.create_registered_model(&model).await.unwrap();
+        let models = client
+            .list_registered_models("unity", "default", None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            models.registered_models[0].full_name.as_deref(),
+            Some("unity.default.churn")
+        );
+        client
+            .delete_registered_model("unity.default.churn", true)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_built_client_returns_serializable_responses() {
+        let server = MockServer::start().await;
+        let table = json!({
+            "name": "sales",
+            "catalog_name": "unity",
+            "schema_name": "default",
+            "table_type": "EXTERNAL",
+            "data_source_format": "DELTA",
+            "columns": [{ "name": "id", "type_name": "LONG", "nullable": false }],
+        });
+        mock(
+            &server,
+            Mock::given(method("GET"))
+                .and(path("/tables/unity.default.sales"))
+                .and(header("authorization", "Bearer dapi-123")),
+            table,
+        )
+        .await;
+
+        let client = UnityRestClient::builder(server.uri())
+            .with_credentials(Arc::new(StaticToken::new("dapi-123")))
+            .with_retry_policy(RetryPolicy::never())
+            .build_rest()
+            .unwrap();
+        let table = client.get_table("unity.default.sales").await.unwrap();
+        let json = serde_json::to_value(&table).unwrap();
+        assert_eq!(json["table_type"], "EXTERNAL");
+        assert_eq!(json["columns"][0]["type_name"], "LONG");
     }
 }
@@ -1,122 +1,771 @@
-use serde::Deserialize;
+//! Request and response bodies of the Unity Catalog REST API
+//!
+//! Fields follow the open source Unity Catalog OpenAPI specification. Response
+//! types are `#[non_exhaustive]`, since the server may return more fields in
+//! the future.
+
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+/// A page of catalogs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ListCatalogsResponse {
+    /// The catalogs on this page.
     pub catalogs: Vec<CatalogInfo>,
+    /// Token for the next page, absent on the last page.
     pub next_page_token: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A catalog, the top level of the three level namespace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct CatalogInfo {
+    /// Unique identifier of the catalog.
     pub id: String,
+    /// Name of the catalog.
     pub name: String,
+    /// User-provided description.
     pub comment: Option<String>,
+    /// User-provided key-value properties.
     #[serde(default)]
     pub properties: HashMap<String, String>,
+    /// Owner of the catalog.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
     pub created_at: Option<i64>,
+    /// Principal that created the catalog.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
     pub updated_at: Option<i64>,
+    /// Principal that last changed the catalog.
+    pub updated_by: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A page of schemas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ListSchemasResponse {
+    /// The schemas on this page.
     pub schemas: Vec<SchemaInfo>,
+    /// Token for the next page, absent on the last page.
     pub next_page_token: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A schema within a catalog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SchemaInfo {
+    /// Unique identifier of the schema.
     pub schema_id: String,
+    /// Name of the schema, relative to its catalog.
     pub name: String,
+    /// Catalog that contains the schema.
     pub catalog_name: Option<String>,
+    /// User-provided description.
     pub comment: Option<String>,
+    /// User-provided key-value properties.
     #[serde(default)]
     pub properties: HashMap<String, String>,
+    /// `catalog.schema`.
     pub full_name: Option<String>,
+    /// Owner of the schema.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
     pub created_at: Option<i64>,
+    /// Principal that created the schema.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
     pub updated_at: Option<i64>,
+    /// Principal that last changed the schema.
+    pub updated_by: Option<String>,
+}
+
+/// Request body of `create_catalog`.
+#[derive(Debug, Serialize)]
+pub struct CreateCatalog {
+    /// Name of the new catalog.
+    pub name: String,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// User-provided key-value properties.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, String>,
+}
+
+/// Changes to a catalog. Fields that are `None` are left unchanged.
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateCatalog {
+    /// New name of the catalog.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    /// New description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Replaces all properties of the catalog.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<HashMap<String, String>>,
+}
+
+/// Request body of `create_schema`.
+#[derive(Debug, Serialize)]
+pub struct CreateSchema {
+    /// Name of the new schema, relative to its catalog.
+    pub name: String,
+    /// Catalog to create the schema in.
+    pub catalog_name: String,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// User-provided key-value properties.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Changes to a schema. Fields that are `None` are left unchanged.
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateSchema {
+    /// New name of the schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    /// New description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Replaces all properties of the schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<HashMap<String, String>>,
+}
+
+/// A page of tables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ListTablesResponse {
+    /// The tables on this page.
     pub tables: Vec<TableInfo>,
+    /// Token for the next page, absent on the last page.
     pub next_page_token: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A table or view within a schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct TableInfo {
+    /// Unique identifier of the table, used to request credentials.
     pub table_id: Option<String>,
+    /// Name of the table, relative to its schema.
     pub name: Option<String>,
+    /// Catalog that contains the table.
     pub catalog_name: Option<String>,
+    /// Schema that contains the table.
     pub schema_name: Option<String>,
+    /// Whether the table is managed, external or a view.
     pub table_type: Option<TableType>,
+    /// Format of the files of the table.
     pub data_source_format: Option<DataSourceFormat>,
+    /// Columns of the table, in no particular order.
     #[serde(default)]
     pub columns: Vec<ColumnInfo>,
+    /// URL of the directory that holds the table.
     pub storage_location: Option<String>,
+    /// SQL query of a view.
+    pub view_definition: Option<String>,
+    /// User-provided description.
     pub comment: Option<String>,
+    /// User-provided key-value properties.
     #[serde(default)]
     pub properties: HashMap<String, String>,
+    /// Owner of the table.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
     pub created_at: Option<i64>,
+    /// Principal that created the table.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
     pub updated_at: Option<i64>,
+    /// Principal that last changed the table.
+    pub updated_by: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+/// Request body of `create_table`.
+#[derive(Debug, Serialize)]
+pub struct CreateTable {
+    /// Name of the new table, relative to its schema.
+    pub name: String,
+    /// Catalog to create the table in.
+    pub catalog_name: String,
+    /// Schema to create the table in.
+    pub schema_name: String,
+    /// Whether the table is managed or external.
+    pub table_type: TableType,
+    /// Format of the files of the table.
+    pub data_source_format: DataSourceFormat,
+    /// Columns of the table.
+    pub columns: Vec<ColumnInfo>,
+    /// URL of the directory that holds the table.
+    pub storage_location: String,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// User-provided key-value properties.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, String>,
+}
+
+/// Whether a table is managed by Unity Catalog, external, or a view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TableType {
+    /// Files are stored and deleted by Unity Catalog.
     Managed,
+    /// Files are stored at a location chosen by the user and kept on drop.
     External,
+    /// A stored query.
+    View,
+    /// A stored query whose result is refreshed by a pipeline.
+    MaterializedView,
+    /// A table that a pipeline appends to continuously.
+    StreamingTable,
 }
 
-#[derive(Debug, Deserialize)]
+/// Format of the files of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DataSourceFormat {
+    /// Delta Lake table.
     Delta,
+    /// Comma separated values.
     Csv,
+    /// Newline delimited JSON.
     Json,
+    /// Apache Avro files.
     Avro,
+    /// Apache Parquet files.
     Parquet,
+    /// Apache ORC files.
     Orc,
+    /// Plain text, one row per line.
     Text,
 }
 
-#[derive(Debug, Deserialize)]
+/// A column of a table.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ColumnInfo {
+    /// Name of the column.
     pub name: Option<String>,
+    /// SQL type, such as `decimal(10,2)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_text: Option<String>,
+    /// Spark JSON representation of the type.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_json: Option<String>,
+    /// Name of the type, without parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_name: Option<ColumnTypeName>,
+    /// Precision of a decimal column.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_precision: Option<i32>,
+    /// Scale of a decimal column.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_scale: Option<i32>,
+    /// Unit of an interval column.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_interval_type: Option<String>,
+    /// Zero-based position of the column in the table.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<i32>,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    /// Whether the column may hold nulls.
     pub nullable: bool,
+    /// Zero-based position of the column among the partition columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub partition_index: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+/// Type of a column or function parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ColumnTypeName {
+    /// `true` or `false`.
     Boolean,
+    /// 8-bit signed integer.
     Byte,
+    /// 16-bit signed integer.
     Short,
+    /// 32-bit signed integer.
     Int,
+    /// 64-bit signed integer.
     Long,
+    /// 32-bit floating point number.
     Float,
+    /// 64-bit floating point number.
     Double,
+    /// Calendar date without a time zone.
     Date,
+    /// Instant in time, with microsecond precision.
     Timestamp,
+    /// Timestamp without a time zone.
     TimestampNtz,
+    /// UTF-8 string.
     String,
+    /// Byte sequence.
     Binary,
+    /// Fixed precision decimal number.
     Decimal,
+    /// Year-month or day-time interval.
     Interval,
+    /// List of values of one type.
     Array,
+    /// Named fields of possibly different types.
     Struct,
+    /// Key-value pairs.
     Map,
+    /// Fixed length string.
     Char,
+    /// Type of the `NULL` literal.
     Null,
+    /// Type defined by the user.
     UserDefinedType,
+    /// Result of a table function.
     TableType,
 }
+
+/// A page of volumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ListVolumesResponse {
+    /// The volumes on this page.
+    pub volumes: Vec<VolumeInfo>,
+    /// Token for the next page, absent on the last page.
+    pub next_page_token: Option<String>,
+}
+
+/// A volume, a directory of non-tabular files within a schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct VolumeInfo {
+    /// Unique identifier of the volume.
+    pub volume_id: Option<String>,
+    /// Name of the volume, relative to its schema.
+    pub name: String,
+    /// Catalog that contains the volume.
+    pub catalog_name: Option<String>,
+    /// Schema that contains the volume.
+    pub schema_name: Option<String>,
+    /// `catalog.schema.volume`.
+    pub full_name: Option<String>,
+    /// Whether the volume is managed or external.
+    pub volume_type: Option<VolumeType>,
+    /// URL of the directory that holds the files.
+    pub storage_location: Option<String>,
+    /// User-provided description.
+    pub comment: Option<String>,
+    /// Owner of the volume.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
+    pub created_at: Option<i64>,
+    /// Principal that created the volume.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
+    pub updated_at: Option<i64>,
+    /// Principal that last changed the volume.
+    pub updated_by: Option<String>,
+}
+
+/// Request body of `create_volume`.
+#[derive(Debug, Serialize)]
+pub struct CreateVolume {
+    /// Name of the new volume, relative to its schema.
+    pub name: String,
+    /// Catalog to create the volume in.
+    pub catalog_name: String,
+    /// Schema to create the volume in.
+    pub schema_name: String,
+    /// Whether the volume is managed or external.
+    pub volume_type: VolumeType,
+    /// Required for external volumes, chosen by the server for managed ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_location: Option<String>,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Changes to a volume. Fields that are `None` are left unchanged.
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateVolume {
+    /// New name of the volume.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    /// New description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Whether a volume is managed by Unity Catalog or external.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VolumeType {
+    /// Files are stored and deleted by Unity Catalog.
+    Managed,
+    /// Files are stored at a location chosen by the user and kept on drop.
+    External,
+}
+
+/// A page of functions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ListFunctionsResponse {
+    /// The functions on this page.
+    pub functions: Vec<FunctionInfo>,
+    /// Token for the next page, absent on the last page.
+    pub next_page_token: Option<String>,
+}
+
+/// A user defined function within a schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct FunctionInfo {
+    /// Unique identifier of the function.
+    pub function_id: Option<String>,
+    /// Name of the function, relative to its schema.
+    pub name: String,
+    /// Catalog that contains the function.
+    pub catalog_name: Option<String>,
+    /// Schema that contains the function.
+    pub schema_name: Option<String>,
+    /// `catalog.schema.function`.
+    pub full_name: Option<String>,
+    /// Parameters of the function.
+    pub input_params: Option<FunctionParameterInfos>,
+    /// Name of the return type, without parameters.
+    pub data_type: Option<ColumnTypeName>,
+    /// SQL return type, such as `decimal(10,2)`.
+    pub full_data_type: Option<String>,
+    /// Columns returned by a table function.
+    pub return_params: Option<FunctionParameterInfos>,
+    /// Language of the function body.
+    pub routine_body: Option<RoutineBody>,
+    /// Body of the function.
+    pub routine_definition: Option<String>,
+    /// How parameters are passed to the function.
+    pub parameter_style: Option<ParameterStyle>,
+    /// Whether the function returns the same result for the same arguments.
+    pub is_deterministic: Option<bool>,
+    /// Whether the function reads data through SQL.
+    pub sql_data_access: Option<SqlDataAccess>,
+    /// Whether the function is called when an argument is null.
+    pub is_null_call: Option<bool>,
+    /// Whose privileges the function runs with.
+    pub security_type: Option<SecurityType>,
+    /// Name of the function within its schema, unique among overloads.
+    pub specific_name: Option<String>,
+    /// Language of an external function body, such as `python`.
+    pub external_language: Option<String>,
+    /// User-provided description.
+    pub comment: Option<String>,
+    /// JSON encoded key-value properties.
+    pub properties: Option<String>,
+    /// Owner of the function.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
+    pub created_at: Option<i64>,
+    /// Principal that created the function.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
+    pub updated_at: Option<i64>,
+    /// Principal that last changed the function.
+    pub updated_by: Option<String>,
+}
+
+/// Request body of `create_function`, which wraps the function in
+/// `function_info`.
+#[derive(Debug, Serialize)]
+pub struct CreateFunctionRequest<'a> {
+    /// The function to create.
+    pub function_info: &'a CreateFunction,
+}
+
+/// A function to register with `create_function`.
+#[derive(Debug, Serialize)]
+pub struct CreateFunction {
+    /// Name of the new function, relative to its schema.
+    pub name: String,
+    /// Catalog to create the function in.
+    pub catalog_name: String,
+    /// Schema to create the function in.
+    pub schema_name: String,
+    /// Parameters of the function.
+    pub input_params: FunctionParameterInfos,
+    /// Name of the return type, without parameters.
+    pub data_type: ColumnTypeName,
+    /// SQL return type, such as `decimal(10,2)`.
+    pub full_data_type: String,
+    /// Columns returned by a table function.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_params: Option<FunctionParameterInfos>,
+    /// Language of the function body.
+    pub routine_body: RoutineBody,
+    /// Body of the function.
+    pub routine_definition: String,
+    /// How parameters are passed to the function.
+    pub parameter_style: ParameterStyle,
+    /// Whether the function returns the same result for the same arguments.
+    pub is_deterministic: bool,
+    /// Whether the function reads data through SQL.
+    pub sql_data_access: SqlDataAccess,
+    /// Whether the function is called when an argument is null.
+    pub is_null_call: bool,
+    /// Whose privileges the function runs with.
+    pub security_type: SecurityType,
+    /// Name of the function within its schema, unique among overloads.
+    pub specific_name: String,
+    /// Language of an external function body, such as `python`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_language: Option<String>,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// JSON encoded key-value properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<String>,
+}
+
+/// Parameters of a function, or columns of a table function.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FunctionParameterInfos {
+    /// The parameters, in no particular order.
+    #[serde(default)]
+    pub parameters: Vec<FunctionParameterInfo>,
+}
+
+/// A parameter of a function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionParameterInfo {
+    /// Name of the parameter.
+    pub name: String,
+    /// SQL type, such as `decimal(10,2)`.
+    pub type_text: String,
+    /// Spark JSON representation of the type.
+    pub type_json: String,
+    /// Name of the type, without parameters.
+    pub type_name: ColumnTypeName,
+    /// Precision of a decimal parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_precision: Option<i32>,
+    /// Scale of a decimal parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_scale: Option<i32>,
+    /// Unit of an interval parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_interval_type: Option<String>,
+    /// Zero-based position of the parameter.
+    pub position: i32,
+    /// Direction of the parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_mode: Option<FunctionParameterMode>,
+    /// Whether this is a parameter or a result column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_type: Option<FunctionParameterType>,
+    /// SQL expression of the default value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_default: Option<String>,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Language of the function body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoutineBody {
+    /// A SQL expression or query.
+    Sql,
+    /// Code in the language named by `external_language`.
+    External,
+}
+
+/// How parameters are passed to a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ParameterStyle {
+    /// SQL parameter passing, the only style defined.
+    S,
+}
+
+/// Whether a function reads data through SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SqlDataAccess {
+    /// The function runs SQL but does not read tables.
+    ContainsSql,
+    /// The function reads tables.
+    ReadsSqlData,
+    /// The function runs no SQL.
+    NoSql,
+}
+
+/// Whose privileges a function runs with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SecurityType {
+    /// The privileges of the owner of the function.
+    Definer,
+}
+
+/// Direction of a function parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FunctionParameterMode {
+    /// An argument passed to the function.
+    In,
+}
+
+/// Whether a function parameter is a parameter or a result column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FunctionParameterType {
+    /// An argument of the function.
+    Param,
+    /// A column returned by a table function.
+    Column,
+}
+
+/// A page of registered models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ListRegisteredModelsResponse {
+    /// The models on this page.
+    pub registered_models: Vec<RegisteredModelInfo>,
+    /// Token for the next page, absent on the last page.
+    pub next_page_token: Option<String>,
+}
+
+/// A machine learning model registered within a schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RegisteredModelInfo {
+    /// Unique identifier of the model.
+    pub id: Option<String>,
+    /// Name of the model, relative to its schema.
+    pub name: String,
+    /// Catalog that contains the model.
+    pub catalog_name: Option<String>,
+    /// Schema that contains the model.
+    pub schema_name: Option<String>,
+    /// `catalog.schema.model`.
+    pub full_name: Option<String>,
+    /// URL of the directory that holds the model versions.
+    pub storage_location: Option<String>,
+    /// User-provided description.
+    pub comment: Option<String>,
+    /// Owner of the model.
+    pub owner: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
+    pub created_at: Option<i64>,
+    /// Principal that created the model.
+    pub created_by: Option<String>,
+    /// Time of the last change in milliseconds since the Unix epoch.
+    pub updated_at: Option<i64>,
+    /// Principal that last changed the model.
+    pub updated_by: Option<String>,
+}
+
+/// Request body of `create_registered_model`.
+#[derive(Debug, Serialize)]
+pub struct CreateRegisteredModel {
+    /// Name of the new model, relative to its schema.
+    pub name: String,
+    /// Catalog to create the model in.
+    pub catalog_name: String,
+    /// Schema to create the model in.
+    pub schema_name: String,
+    /// User-provided description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Changes to a registered model. Fields that are `None` are left unchanged.
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateRegisteredModel {
+    /// New name of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    /// New description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Request body of `generate_temporary_table_credentials`.
+#[derive(Debug, Serialize)]
+pub struct GenerateTemporaryTableCredential {
+    /// Unique identifier of the table, see [`TableInfo::table_id`].
+    pub table_id: String,
+    /// What the credentials are used for.
+    pub operation: TableOperation,
+}
+
+/// Operations that temporary table credentials allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TableOperation {
+    /// Reading the files of the table.
+    Read,
+    /// Reading and writing the files of the table.
+    ReadWrite,
+}
+
+/// Short-lived credentials for the storage location of a table. Only the
+/// field of the storage system that holds the table is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TemporaryCredentials {
+    /// Credentials for Amazon S3.
+    pub aws_temp_credentials: Option<AwsCredentials>,
+    /// Credentials for Azure storage.
+    pub azure_user_delegation_sas: Option<AzureUserDelegationSas>,
+    /// Credentials for Google Cloud Storage.
+    pub gcp_oauth_token: Option<GcpOauthToken>,
+    /// Expiration time in milliseconds since the Unix epoch.
+    pub expiration_time: Option<i64>,
+}
+
+/// Temporary credentials for Amazon S3.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AwsCredentials {
+    /// Access key ID.
+    pub access_key_id: String,
+    /// Secret access key.
+    pub secret_access_key: String,
+    /// Session token that has to accompany temporary keys.
+    pub session_token: Option<String>,
+}
+
+/// Shared access signature for Azure storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AzureUserDelegationSas {
+    /// The signature, as a URL query string.
+    pub sas_token: String,
+}
+
+/// OAuth token for Google Cloud Storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct GcpOauthToken {
+    /// The bearer token.
+    pub oauth_token: String,
+}
@@ -0,0 +1,157 @@
+//! Retries of failed requests against Unity Catalog
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+use chrono::DateTime;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+
+/// How requests that fail with a transient error are retried.
+///
+/// Requests are retried when the server answers `408`, `429`, `502`, `503`
+/// or `504`, or when the response does not arrive in time. Only idempotent
+/// requests (`GET`, `PUT` and `DELETE`) are retried, since the server may
+/// have applied a request before failing. Requests that could not connect
+/// never reached the server and are retried regardless of their method.
+///
+/// The wait before the `n`-th retry grows exponentially from the initial
+/// backoff up to the maximum backoff, and a random half of it is skipped so
+/// that clients failing together do not retry together. A `Retry-After`
+/// header replaces the computed wait; if it asks for more than the maximum
+/// backoff, the request fails instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy with default values: 3 retries, backing off from
+    /// 200 milliseconds up to 10 seconds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy that fails on the first error.
+    pub fn never() -> Self {
+        Self::default().with_max_retries(0)
+    }
+
+    /// Retry a request at most `max_retries` times.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait up to `backoff` before the first retry.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Never wait longer than `backoff` before a retry.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// The wait before retry number `retry`, counting from zero, or `None`
+    /// if the request should not be retried.
+    pub(crate) fn backoff(&self, retry: u32, headers: Option<&HeaderMap>) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        if let Some(retry_after) = headers.and_then(retry_after) {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        Some(backoff / 2 + backoff.mul_f64(random() / 2.0))
+    }
+}
+
+/// Whether a response with `status` is worth retrying.
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// The wait requested by a `Retry-After` header, given either in seconds
+/// or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        SystemTime::from(date)
+            .duration_since(SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+/// A random number in `[0, 1)`, good enough to spread out retries.
+fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_honors_retry_after() {
+        let policy = RetryPolicy::new()
+            .with_max_retries(4)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(300));
+
+        let backoff = |retry| policy.backoff(retry, None).unwrap();
+        assert!((50..=100).contains(&backoff(0).as_millis()));
+        assert!((100..=200).contains(&backoff(1).as_millis()));
+        assert!((150..=300).contains(&backoff(3).as_millis()));
+        assert_eq!(policy.backoff(4, None), None);
+        assert_eq!(RetryPolicy::never().backoff(0, None), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("0"));
+        assert_eq!(policy.backoff(0, Some(&headers)), Some(Duration::ZERO));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+        assert_eq!(policy.backoff(0, Some(&headers)), None);
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(policy.backoff(0, Some(&headers)), Some(Duration::ZERO));
+    }
+}
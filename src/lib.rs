@@ -26,13 +26,30 @@
 //! # Ok::<(), Box<dyn std::error::Error + Send + Sync + 'static>>(()) };
 //! # Ok(()) }
 //! ```
+//!
+//! The REST client underneath is available in the [`client`] module, for
+//! managing Unity Catalog objects outside of DataFusion.
 
 #![warn(missing_docs)]
 
-mod client;
+pub mod client;
+#[cfg(test)]
+mod test_util;
 mod unity;
 
-pub use unity::{error::UnityError, Unity};
+pub use client::auth::{
+    CredentialError, CredentialProvider, EnvToken, OAuthClientCredentials, StaticToken,
+    UC_TOKEN_ENV,
+};
+pub use client::error::ApiError;
+pub use client::retry::RetryPolicy;
+pub use unity::{
+    builder::{UnityBuilder, UC_ENDPOINT_ENV},
+    dml::{enable_dml, execute_merge},
+    error::UnityError,
+    options::{CatalogRegistration, ErrorPolicy, LoadingMode, UnityOptions},
+    Unity,
+};
 
 #[cfg(test)]
 mod test {
@@ -42,6 +59,7 @@ mod test {
     use datafusion::prelude::*;
 
     #[tokio::test]
+    #[ignore = "requires a Unity Catalog reference server on 127.0.0.1:8080"]
     async fn test_list_catalogs() {
         tracing_subscriber::fmt::init();
 
@@ -75,7 +93,5 @@ mod test {
             .show()
             .await
             .unwrap();
-
-        assert!(false)
     }
 }
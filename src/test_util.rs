@@ -0,0 +1,180 @@
+//! Helpers for tests that run against a stub Unity Catalog server
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use deltalake::{
+    arrow::{
+        array::Int64Array,
+        datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema},
+        record_batch::RecordBatch,
+    },
+    kernel::{DataType, StructField},
+    logstore::{logstores, LogStoreFactory},
+    storage::{
+        factories, object_store::local::LocalFileSystem, ObjectStoreFactory, ObjectStoreRef, Path,
+        StorageOptions,
+    },
+    DeltaOps, DeltaResult, DeltaTable,
+};
+use serde_json::{json, Value};
+use url::Url;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+pub async fn mock_catalogs(server: &MockServer, names: &[&str]) {
+    let catalogs: Vec<Value> = names
+        .iter()
+        .map(|name| json!({ "id": name, "name": name }))
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/catalogs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "catalogs": catalogs })))
+        .mount(server)
+        .await;
+}
+
+pub async fn mock_schemas(server: &MockServer, catalog_name: &str, names: &[&str]) {
+    let schemas: Vec<Value> = names
+        .iter()
+        .map(|name| json!({ "schema_id": name, "name": name, "catalog_name": catalog_name }))
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/schemas"))
+        .and(query_param("catalog_name", catalog_name))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "schemas": schemas })))
+        .mount(server)
+        .await;
+}
+
+pub async fn mock_tables(
+    server: &MockServer,
+    catalog_name: &str,
+    schema_name: &str,
+    response: ResponseTemplate,
+) {
+    Mock::given(method("GET"))
+        .and(path("/tables"))
+        .and(query_param("catalog_name", catalog_name))
+        .and(query_param("schema_name", schema_name))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+pub fn tables_response(tables: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "tables": tables }))
+}
+
+pub async fn mock_table(server: &MockServer, full_name: &str, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(path(format!("/tables/{}", full_name)))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+pub async fn requested_paths(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect()
+}
+
+/// Create an empty Delta table with a single nullable `id BIGINT` column.
+pub async fn create_delta_table(location: &str) -> DeltaTable {
+    DeltaOps::try_from_uri(location)
+        .await
+        .unwrap()
+        .create()
+        .with_columns([StructField::new("id", DataType::LONG, true)])
+        .await
+        .unwrap()
+}
+
+/// Append `ids` to a table created by [`create_delta_table`].
+pub async fn append_ids(table: DeltaTable, ids: &[i64]) -> DeltaTable {
+    let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+        "id",
+        ArrowDataType::Int64,
+        true,
+    )]));
+    let batch =
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(ids.to_vec()))]).unwrap();
+    DeltaOps(table).write([batch]).await.unwrap()
+}
+
+/// Scheme of [`remote_location`].
+const STUB_SCHEME: &str = "unity-stub";
+
+/// Opens stores for [`remote_location`] in the local file system, recording
+/// the storage options they are opened with.
+#[derive(Default)]
+struct StubStorage {
+    /// Storage options by directory, oldest first.
+    opened: Mutex<HashMap<String, Vec<HashMap<String, String>>>>,
+}
+
+impl ObjectStoreFactory for StubStorage {
+    fn parse_url_opts(
+        &self,
+        url: &Url,
+        options: &StorageOptions,
+    ) -> DeltaResult<(ObjectStoreRef, Path)> {
+        self.opened
+            .lock()
+            .unwrap()
+            .entry(url.path().trim_end_matches('/').to_string())
+            .or_default()
+            .push(options.0.clone());
+        let store = LocalFileSystem::new_with_prefix(url.path())?;
+        Ok((Arc::new(store), Path::from("/")))
+    }
+}
+
+impl LogStoreFactory for StubStorage {}
+
+fn stub_storage() -> &'static Arc<StubStorage> {
+    static STORAGE: OnceLock<Arc<StubStorage>> = OnceLock::new();
+    STORAGE.get_or_init(|| {
+        let storage = Arc::new(StubStorage::default());
+        let scheme = Url::parse(&format!("{}://", STUB_SCHEME)).unwrap();
+        factories().insert(scheme.clone(), storage.clone());
+        logstores().insert(scheme, storage.clone());
+        storage
+    })
+}
+
+/// A location for the local directory `path` that looks like object storage,
+/// so that credentials are vended for tables stored there.
+pub fn remote_location(path: &str) -> String {
+    stub_storage();
+    format!("{}://{}", STUB_SCHEME, path)
+}
+
+/// The storage options of the stores opened for [`remote_location`]`(path)`,
+/// oldest first.
+pub fn storage_options_of(path: &str) -> Vec<HashMap<String, String>> {
+    let opened = stub_storage().opened.lock().unwrap();
+    opened.get(path).cloned().unwrap_or_default()
+}
+
+/// Vended AWS credentials with the given key that expire at `expiration_time`,
+/// in milliseconds since the Unix epoch.
+pub fn aws_credentials_response(access_key_id: &str, expiration_time: i64) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "aws_temp_credentials": {
+            "access_key_id": access_key_id,
+            "secret_access_key": "secret",
+            "session_token": "session",
+        },
+        "expiration_time": expiration_time,
+    }))
+}
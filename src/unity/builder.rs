@@ -0,0 +1,445 @@
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use serde::Deserialize;
+
+use crate::client::{
+    auth::{EnvToken, OAuthClientCredentials, StaticToken, UC_TOKEN_ENV},
+    UnityClient, UnityClientBuilder,
+};
+
+use super::{
+    error::UnityError,
+    options::{LoadingMode, UnityOptions},
+    Unity,
+};
+
+/// Environment variable read by [`UnityBuilder::with_env`] for the endpoint.
+pub const UC_ENDPOINT_ENV: &str = "UC_ENDPOINT";
+
+/// Configures and creates a [`Unity`] instance.
+///
+/// Settings can be given in code, read from the environment with
+/// [`with_env`](Self::with_env) or read from a TOML file with
+/// [`with_config_file`](Self::with_config_file). Every call overrides the
+/// settings made by earlier calls, so explicit values should come last.
+///
+/// # Configuration file
+/// All keys are optional:
+/// ```toml
+/// endpoint = "https://example.cloud.databricks.com/api/2.1/unity-catalog/"
+/// token = "dapi-123"
+/// loading_mode = "lazy"                # or "eager"
+/// include_catalogs = ["main", "sales"]
+/// exclude_catalogs = ["sales"]
+/// catalog_ttl_secs = 300
+/// schema_ttl_secs = 300
+/// table_ttl_secs = 60
+/// request_timeout_secs = 30
+/// connect_timeout_secs = 5
+/// max_retries = 5
+/// credential_vending = true
+/// managed_location = "s3://bucket/managed"
+///
+/// # instead of `token`
+/// [oauth]
+/// token_url = "https://example.cloud.databricks.com/oidc/v1/token"
+/// client_id = "client-id"
+/// client_secret = "client-secret"
+/// scope = "all-apis"
+///
+/// [storage_options]
+/// AWS_REGION = "eu-west-1"
+/// ```
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # async {
+/// # use datafusion_unity_catalog::UnityError;
+/// use std::time::Duration;
+/// use datafusion_unity_catalog::UnityBuilder;
+///
+/// let unity_catalog = UnityBuilder::from_env()
+///     .with_config_file("unity.toml")?
+///     .with_options(|options| options.with_table_ttl(Duration::from_secs(60)))
+///     .build()
+///     .await?;
+/// # Ok::<(), UnityError>(()) };
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Default)]
+pub struct UnityBuilder {
+    endpoint: Option<String>,
+    client: Option<UnityClient>,
+    options: UnityOptions,
+}
+
+impl UnityBuilder {
+    /// Start configuring a [`Unity`] instance with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start configuring a [`Unity`] instance from the environment, see
+    /// [`with_env`](Self::with_env).
+    pub fn from_env() -> Self {
+        Self::new().with_env()
+    }
+
+    /// Start configuring a [`Unity`] instance from the TOML file at `path`,
+    /// see [`with_config_file`](Self::with_config_file).
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, UnityError> {
+        Self::new().with_config_file(path)
+    }
+
+    /// Read the endpoint from `UC_ENDPOINT` and, if `UC_TOKEN` is set,
+    /// authenticate with the token in `UC_TOKEN`.
+    ///
+    /// The token is read again for every request, so it can be rotated
+    /// without recreating the instance. Unset variables are ignored.
+    pub fn with_env(self) -> Self {
+        let mut builder = self.with_env_from(|key| std::env::var(key).ok());
+        if std::env::var_os(UC_TOKEN_ENV).is_some() {
+            builder = builder.with_client_builder(|client| {
+                client.with_credentials(Arc::new(EnvToken::default()))
+            });
+        }
+        builder
+    }
+
+    /// Like [`with_env`](Self::with_env), but look up the variables with
+    /// `get`, which returns `None` for unset variables. The token is read
+    /// once.
+    pub fn with_env_from(mut self, get: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(endpoint) = get(UC_ENDPOINT_ENV) {
+            self.endpoint = Some(endpoint);
+        }
+        if let Some(token) = get(UC_TOKEN_ENV) {
+            self = self.with_client_builder(|client| {
+                client.with_credentials(Arc::new(StaticToken::new(token)))
+            });
+        }
+        self
+    }
+
+    /// Apply the settings in the TOML file at `path`.
+    pub fn with_config_file(self, path: impl AsRef<Path>) -> Result<Self, UnityError> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(|e| UnityError::Config {
+            message: format!("cannot read {}: {}", path.display(), e),
+        })?;
+        self.with_config(&config).map_err(|e| match e {
+            UnityError::Config { message } => UnityError::Config {
+                message: format!("invalid config {}: {}", path.display(), message),
+            },
+            e => e,
+        })
+    }
+
+    /// Apply the settings in `config`, given in the format of the
+    /// [configuration file](Self#configuration-file).
+    pub fn with_config(mut self, config: &str) -> Result<Self, UnityError> {
+        let config: Config = toml::from_str(config).map_err(|e| UnityError::Config {
+            message: e.message().to_string(),
+        })?;
+        let seconds = Duration::from_secs;
+
+        if let Some(endpoint) = config.endpoint {
+            self.endpoint = Some(endpoint);
+        }
+        let mut options = self.options;
+        match (config.token, config.oauth) {
+            (Some(_), Some(_)) => {
+                return Err(UnityError::Config {
+                    message: "set either `token` or `[oauth]`, not both".to_string(),
+                })
+            }
+            (Some(token), None) => {
+                options.client = options
+                    .client
+                    .with_credentials(Arc::new(StaticToken::new(token)));
+            }
+            (None, Some(oauth)) => {
+                let mut credentials = OAuthClientCredentials::new(
+                    oauth.token_url,
+                    oauth.client_id,
+                    oauth.client_secret,
+                );
+                if let Some(scope) = oauth.scope {
+                    credentials = credentials.with_scope(scope);
+                }
+                options.client = options.client.with_credentials(Arc::new(credentials));
+            }
+            (None, None) => {}
+        }
+        if let Some(loading_mode) = config.loading_mode {
+            options = options.with_loading_mode(match loading_mode.as_str() {
+                "eager" => LoadingMode::Eager,
+                "lazy" => LoadingMode::Lazy,
+                other => {
+                    return Err(UnityError::Config {
+                        message: format!(
+                            "unknown loading_mode '{}', expected 'eager' or 'lazy'",
+                            other
+                        ),
+                    })
+                }
+            });
+        }
+        if let Some(names) = config.include_catalogs {
+            options = options.with_included_catalogs(names);
+        }
+        if let Some(names) = config.exclude_catalogs {
+            options = options.with_excluded_catalogs(names);
+        }
+        if let Some(ttl) = config.catalog_ttl_secs {
+            options = options.with_catalog_ttl(seconds(ttl));
+        }
+        if let Some(ttl) = config.schema_ttl_secs {
+            options = options.with_schema_ttl(seconds(ttl));
+        }
+        if let Some(ttl) = config.table_ttl_secs {
+            options = options.with_table_ttl(seconds(ttl));
+        }
+        if let Some(timeout) = config.request_timeout_secs {
+            options.client = options.client.with_request_timeout(Some(seconds(timeout)));
+        }
+        if let Some(timeout) = config.connect_timeout_secs {
+            options.client = options.client.with_connect_timeout(Some(seconds(timeout)));
+        }
+        if let Some(max_retries) = config.max_retries {
+            let retry_policy = options.client.http.retry_policy.clone();
+            options.client = options
+                .client
+                .with_retry_policy(retry_policy.with_max_retries(max_retries));
+        }
+        if let Some(enabled) = config.credential_vending {
+            options = options.with_credential_vending(enabled);
+        }
+        if let Some(location) = config.managed_location {
+            options = options.with_managed_location(location);
+        }
+        if let Some(storage_options) = config.storage_options {
+            options = options.with_storage_options(storage_options);
+        }
+        self.options = options;
+        Ok(self)
+    }
+
+    /// Connect to the Unity Catalog REST API at `endpoint`, e.g.
+    /// `http://localhost:8080/api/2.1/unity-catalog/`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Change the [`UnityOptions`] set so far, e.g. the cache TTLs or the
+    /// catalogs to expose.
+    pub fn with_options(mut self, f: impl FnOnce(UnityOptions) -> UnityOptions) -> Self {
+        self.options = f(self.options);
+        self
+    }
+
+    /// Talk to Unity Catalog through `client`.
+    ///
+    /// The endpoint, credentials, HTTP client, timeouts and retry policy
+    /// configured on the builder are ignored, since they only apply to the
+    /// client the builder creates.
+    pub fn with_client(mut self, client: UnityClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Change the settings of the client the builder creates, e.g. the
+    /// credentials, timeouts or retry policy, see
+    /// [`UnityOptions::with_client_builder`].
+    pub fn with_client_builder(
+        self,
+        f: impl FnOnce(UnityClientBuilder) -> UnityClientBuilder,
+    ) -> Self {
+        self.with_options(|options| options.with_client_builder(f))
+    }
+
+    /// Create the [`Unity`] instance and load its catalogs.
+    pub async fn build(self) -> Result<Unity, UnityError> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let endpoint = self.endpoint.ok_or_else(|| UnityError::Config {
+                    message: format!(
+                        "no Unity Catalog endpoint configured, set ${} or call with_endpoint",
+                        UC_ENDPOINT_ENV
+                    ),
+                })?;
+                self.options
+                    .client
+                    .clone()
+                    .with_endpoint(endpoint)
+                    .build()?
+            }
+        };
+        Unity::try_new_with_client(client, self.options).await
+    }
+}
+
+/// Settings read from a configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    endpoint: Option<String>,
+    token: Option<String>,
+    oauth: Option<OAuthConfig>,
+    loading_mode: Option<String>,
+    include_catalogs: Option<Vec<String>>,
+    exclude_catalogs: Option<Vec<String>>,
+    catalog_ttl_secs: Option<u64>,
+    schema_ttl_secs: Option<u64>,
+    table_ttl_secs: Option<u64>,
+    request_timeout_secs: Option<u64>,
+    connect_timeout_secs: Option<u64>,
+    max_retries: Option<u32>,
+    credential_vending: Option<bool>,
+    managed_location: Option<String>,
+    storage_options: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OAuthConfig {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::catalog::CatalogProviderList;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::{client::retry::RetryPolicy, test_util::*};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build_from_config_file() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["main", "sales", "scratch"]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("unity.toml");
+        std::fs::write(
+            &config,
+            format!(
+                r#"
+                endpoint = "{}"
+                token = "secret"
+                loading_mode = "lazy"
+                include_catalogs = ["main", "sales"]
+                exclude_catalogs = ["sales"]
+                table_ttl_secs = 60
+
+                [storage_options]
+                AWS_REGION = "eu-west-1"
+                "#,
+                server.uri()
+            ),
+        )
+        .unwrap();
+
+        let builder = UnityBuilder::from_config_file(&config).unwrap();
+        assert_eq!(builder.options.loading_mode, LoadingMode::Lazy);
+        assert_eq!(builder.options.table_ttl, Some(Duration::from_secs(60)));
+        assert_eq!(
+            builder.options.storage_options,
+            HashMap::from([("AWS_REGION".to_string(), "eu-west-1".to_string())])
+        );
+
+        let unity = builder.build().await.unwrap();
+        assert_eq!(unity.catalog_names(), vec!["main"]);
+        assert!(unity.catalog("scratch").is_none());
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1, "lazy loading only lists catalogs");
+        assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build_from_env() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/catalogs"))
+            .and(header("authorization", "Bearer from-env"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "catalogs": [{ "id": "main", "name": "main" }] }),
+            ))
+            .mount(&server)
+            .await;
+
+        let env = HashMap::from([
+            (UC_ENDPOINT_ENV, "http://unreachable.invalid/".to_string()),
+            (UC_TOKEN_ENV, "from-env".to_string()),
+        ]);
+        let builder = UnityBuilder::new().with_env_from(|key| env.get(key).cloned());
+        assert_eq!(
+            builder.endpoint.as_deref(),
+            Some("http://unreachable.invalid/")
+        );
+
+        // explicit settings override the environment
+        let unity = builder
+            .with_endpoint(server.uri())
+            .with_options(|options| options.with_loading_mode(LoadingMode::Lazy))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(unity.catalog_names(), vec!["main"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build_with_client_builder() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["main"]).await;
+
+        let unity = UnityBuilder::new()
+            .with_config("token = 'from-config'\nrequest_timeout_secs = 5")
+            .unwrap()
+            .with_endpoint(server.uri())
+            .with_client_builder(|client| {
+                client
+                    .with_credentials(Arc::new(StaticToken::new("secret")))
+                    .with_retry_policy(RetryPolicy::never())
+            })
+            .with_options(|options| options.with_loading_mode(LoadingMode::Lazy));
+        // settings of the config file that are not changed are kept
+        assert_eq!(
+            unity.options.client.http.request_timeout,
+            Some(Duration::from_secs(5))
+        );
+
+        let unity = unity.build().await.unwrap();
+        assert_eq!(unity.catalog_names(), vec!["main"]);
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_config_is_rejected() {
+        let config = |config| match UnityBuilder::new().with_config(config) {
+            Err(UnityError::Config { message }) => message,
+            other => panic!("expected a config error, got {:?}", other.map(|_| ())),
+        };
+        assert!(config("endpont = 'x'").contains("unknown field `endpont`"));
+        assert!(config("loading_mode = 'sometimes'").contains("unknown loading_mode"));
+        assert!(config("table_ttl_secs = -1").contains("invalid value"));
+        assert!(config(
+            "token = 'x'\n[oauth]\ntoken_url = 'u'\nclient_id = 'i'\nclient_secret = 's'"
+        )
+        .contains("not both"));
+
+        let Err(UnityError::Config { message }) = UnityBuilder::new().build().await else {
+            panic!("expected a missing endpoint to fail");
+        };
+        assert!(message.contains(UC_ENDPOINT_ENV));
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    sync::{PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+
+/// Children of the metastore, a catalog or a schema, keyed by name.
+///
+/// The cache remembers when the children were last listed so callers can
+/// decide whether to list them again. Entries added individually expire on
+/// their own once they are older than the TTL.
+pub(crate) struct MetadataCache<V> {
+    entries: DashMap<String, Entry<V>>,
+    listed_at: RwLock<Option<Instant>>,
+    ttl: Option<Duration>,
+}
+
+struct Entry<V> {
+    value: V,
+    loaded_at: Instant,
+}
+
+impl<V: Clone> MetadataCache<V> {
+    /// Create an empty cache whose contents expire after `ttl`, or never.
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            entries: DashMap::new(),
+            listed_at: RwLock::new(None),
+            ttl,
+        }
+    }
+
+    fn is_expired(&self, loaded_at: Instant) -> bool {
+        self.ttl.is_some_and(|ttl| loaded_at.elapsed() >= ttl)
+    }
+
+    /// Whether the cache holds a complete listing, possibly an expired one.
+    pub fn is_listed(&self) -> bool {
+        self.listed_at
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Whether the cache holds a complete listing that has not expired.
+    pub fn is_fresh(&self) -> bool {
+        self.listed_at
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some_and(|listed_at| !self.is_expired(listed_at))
+    }
+
+    /// Look up `name`, ignoring entries that have expired.
+    pub fn get(&self, name: &str) -> Option<V> {
+        self.entries
+            .get(name)
+            .filter(|entry| !self.is_expired(entry.loaded_at))
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Look up `name`, including entries that have expired.
+    pub fn get_stale(&self, name: &str) -> Option<V> {
+        self.entries.get(name).map(|entry| entry.value.clone())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    pub fn values(&self) -> Vec<V> {
+        self.entries
+            .iter()
+            .map(|entry| entry.value.clone())
+            .collect()
+    }
+
+    pub fn insert(&self, name: String, value: V) -> Option<V> {
+        let entry = Entry {
+            value,
+            loaded_at: Instant::now(),
+        };
+        self.entries.insert(name, entry).map(|entry| entry.value)
+    }
+
+    pub fn remove(&self, name: &str) -> Option<V> {
+        self.entries.remove(name).map(|(_, entry)| entry.value)
+    }
+
+    /// Replace the contents with a complete listing.
+    pub fn replace(&self, listing: impl IntoIterator<Item = (String, V)>) {
+        let now = Instant::now();
+        let listing: Vec<_> = listing.into_iter().collect();
+        self.entries
+            .retain(|name, _| listing.iter().any(|(listed, _)| listed == name));
+        for (name, value) in listing {
+            let entry = Entry {
+                value,
+                loaded_at: now,
+            };
+            self.entries.insert(name, entry);
+        }
+        *self
+            .listed_at
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(now);
+    }
+
+    /// Drop all entries and forget the last listing.
+    pub fn clear(&self) {
+        self.entries.clear();
+        *self
+            .listed_at
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_replace_keeps_only_listed_entries() {
+        let cache = MetadataCache::new(None);
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+        assert!(!cache.is_listed());
+
+        cache.replace([("b".to_string(), 3), ("c".to_string(), 4)]);
+        assert!(cache.is_fresh());
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(3));
+        assert_eq!(cache.get("c"), Some(4));
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let cache = MetadataCache::new(Some(Duration::ZERO));
+        cache.replace([("a".to_string(), 1)]);
+
+        assert!(cache.is_listed());
+        assert!(!cache.is_fresh());
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get_stale("a"), Some(1));
+    }
+}
@@ -1,42 +1,143 @@
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{
+    any::Any,
+    sync::{Arc, Weak},
+};
 
-use datafusion::catalog::{schema::SchemaProvider, CatalogProvider};
+use datafusion::{
+    catalog::{schema::SchemaProvider, CatalogProvider, CatalogProviderList},
+    error::DataFusionError,
+};
 
 use crate::{client::UnityClient, unity::schema::UnitySchema};
 
-use super::error::UnityError;
+use super::{
+    cache::MetadataCache,
+    error::UnityError,
+    options::{LoadingMode, UnityOptions},
+    runtime::block_on,
+    table::cache::DeltaTableCache,
+};
 
 pub struct Catalog {
     name: String,
     client: Arc<UnityClient>,
-    schemas: HashMap<String, Arc<dyn SchemaProvider>>,
+    options: Arc<UnityOptions>,
+    tables: Arc<DeltaTableCache>,
+    catalog_list: Weak<dyn CatalogProviderList>,
+    schemas: MetadataCache<Arc<UnitySchema>>,
 }
 
 impl Catalog {
     pub async fn try_new(
         client: Arc<UnityClient>,
+        options: Arc<UnityOptions>,
+        tables: Arc<DeltaTableCache>,
+        catalog_list: Weak<dyn CatalogProviderList>,
         catalog_name: &str,
     ) -> Result<Catalog, UnityError> {
-        let mut catalog = Catalog {
+        let catalog = Catalog {
             name: catalog_name.to_string(),
             client: client.clone(),
-            schemas: HashMap::new(),
+            schemas: MetadataCache::new(options.schema_ttl),
+            options,
+            tables,
+            catalog_list,
         };
-        catalog.fetch().await?;
+        if catalog.options.loading_mode == LoadingMode::Eager {
+            catalog.fetch().await?;
+        }
         Ok(catalog)
     }
 
-    async fn fetch(&mut self) -> Result<(), UnityError> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(&self) -> Result<(), UnityError> {
         let schemas = self.client.list_schemas(&self.name).await?;
+        let mut listing = Vec::new();
         for schema in schemas {
-            let provider =
-                UnitySchema::try_new(self.client.clone(), &self.name, schema.name()).await?;
-            self.schemas
-                .insert(schema.name().to_owned(), Arc::new(provider));
+            let provider = match self.schemas.get_stale(schema.name()) {
+                Some(provider) => provider,
+                None => {
+                    let provider = UnitySchema::try_new(
+                        self.client.clone(),
+                        self.options.clone(),
+                        self.tables.clone(),
+                        self.catalog_list.clone(),
+                        &self.name,
+                        schema.name(),
+                    )
+                    .await;
+                    let full_name = format!("{}.{}", self.name, schema.name());
+                    match self.options.error_policy.apply(&full_name, provider)? {
+                        Some(provider) => Arc::new(provider),
+                        None => continue,
+                    }
+                }
+            };
+            listing.push((schema.name().to_owned(), provider));
+        }
+        self.schemas.replace(listing);
+
+        Ok(())
+    }
+
+    /// Reload the schemas of this catalog, if they have been loaded before,
+    /// and the tables of those schemas.
+    pub async fn refresh(&self) -> Result<(), UnityError> {
+        if !self.schemas.is_listed() {
+            return Ok(());
+        }
+
+        self.fetch().await?;
+        for schema in self.schemas.values() {
+            let result = schema.refresh().await;
+            self.options
+                .error_policy
+                .apply(&schema.full_name(), result)?;
         }
 
         Ok(())
     }
+
+    /// Reload the tables of schema `name`, listing schemas again if it is not known yet.
+    pub async fn refresh_schema(&self, name: &str) -> Result<(), UnityError> {
+        match self.find_schema(name).await? {
+            Some(schema) => schema.refresh().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Find schema `name`, listing schemas again if it is not known yet.
+    pub async fn find_schema(&self, name: &str) -> Result<Option<Arc<UnitySchema>>, UnityError> {
+        if !self.schemas.contains(name) {
+            self.fetch().await?;
+        }
+        Ok(self.schemas.get_stale(name))
+    }
+
+    async fn create_schema(&self, name: &str) -> Result<Arc<UnitySchema>, UnityError> {
+        let schema = self.client.create_schema(&self.name, name).await?;
+        let provider = UnitySchema::try_new(
+            self.client.clone(),
+            self.options.clone(),
+            self.tables.clone(),
+            self.catalog_list.clone(),
+            &self.name,
+            schema.name(),
+        )
+        .await?;
+        Ok(Arc::new(provider))
+    }
+
+    fn ensure_fresh(&self) {
+        if !self.schemas.is_fresh() {
+            if let Err(e) = block_on(self.fetch()).and_then(|r| r) {
+                tracing::error!("cannot list schemas of catalog '{}': {}", self.name, e);
+            }
+        }
+    }
 }
 
 impl CatalogProvider for Catalog {
@@ -45,31 +146,54 @@ impl CatalogProvider for Catalog {
     }
 
     fn schema_names(&self) -> Vec<String> {
-        self.schemas.keys().cloned().collect()
+        self.ensure_fresh();
+        self.schemas.names()
     }
 
     fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
-        self.schemas.get(name).cloned()
+        self.ensure_fresh();
+        self.schemas
+            .get_stale(name)
+            .map(|schema| schema as Arc<dyn SchemaProvider>)
     }
 
-    // TODO: implement overwrite trait
-    // fn register_schema(
-    //     &self,
-    //     name: &str,
-    //     schema: Arc<dyn SchemaProvider>,
-    // ) -> Result<Option<Arc<dyn SchemaProvider>>> {
-    //     // use variables to avoid unused variable warnings
-    //     let _ = name;
-    //     let _ = schema;
-    //     not_impl_err!("Registering new schemas is not supported")
-    // }
-
-    // TODO: implement overwrite trait
-    // fn deregister_schema(
-    //     &self,
-    //     _name: &str,
-    //     _cascade: bool,
-    // ) -> Result<Option<Arc<dyn SchemaProvider>>> {
-    //     not_impl_err!("Deregistering new schemas is not supported")
-    // }
+    /// Create schema `name` in Unity Catalog.
+    ///
+    /// DataFusion passes an empty in-memory provider for `CREATE SCHEMA`; it
+    /// is replaced by a provider for the new remote schema.
+    fn register_schema(
+        &self,
+        name: &str,
+        schema: Arc<dyn SchemaProvider>,
+    ) -> Result<Option<Arc<dyn SchemaProvider>>, DataFusionError> {
+        if !schema.table_names().is_empty() {
+            return Err(DataFusionError::NotImplemented(format!(
+                "cannot register schema '{}.{}' with existing tables in Unity Catalog",
+                self.name, name
+            )));
+        }
+
+        let schema = block_on(self.create_schema(name)).and_then(|r| r)?;
+        Ok(self
+            .schemas
+            .insert(name.to_owned(), schema)
+            .map(|schema| schema as Arc<dyn SchemaProvider>))
+    }
+
+    /// Delete schema `name` from Unity Catalog, including its tables if
+    /// `cascade` is set.
+    fn deregister_schema(
+        &self,
+        name: &str,
+        cascade: bool,
+    ) -> Result<Option<Arc<dyn SchemaProvider>>, DataFusionError> {
+        let Some(schema) = self.schema(name) else {
+            return Ok(None);
+        };
+
+        block_on(self.client.delete_schema(&self.name, name, cascade))?
+            .map_err(UnityError::from)?;
+        self.schemas.remove(name);
+        Ok(Some(schema))
+    }
 }
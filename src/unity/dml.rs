@@ -0,0 +1,634 @@
+//! `DELETE`, `UPDATE` and `MERGE INTO` on Unity Delta tables.
+//!
+//! DataFusion plans `DELETE` and `UPDATE` as [`DmlStatement`]s but cannot
+//! execute them. [`enable_dml`] adds an analyzer rule that turns statements on
+//! Unity Delta tables into a [`DeltaDml`] node, which is executed with the
+//! `deltalake` delete and update operations. The rule runs before the
+//! optimizer, which would otherwise push the predicate into the scan.
+//!
+//! DataFusion does not plan `MERGE` at all, so [`execute_merge`] takes the
+//! statement apart itself.
+
+use std::{
+    any::Any,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, UInt64Array},
+        datatypes::{DataType, Field, Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    common::{
+        config::ConfigOptions,
+        tree_node::{Transformed, TransformedResult, TreeNode},
+        DFSchemaRef, TableReference,
+    },
+    datasource::{source_as_provider, TableProvider},
+    error::DataFusionError,
+    execution::{
+        context::{QueryPlanner, SessionState},
+        SendableRecordBatchStream, TaskContext,
+    },
+    logical_expr::{
+        expr_rewriter::unnormalize_col, utils::conjunction, DmlStatement, Extension, LogicalPlan,
+        UserDefinedLogicalNode, UserDefinedLogicalNodeCore, WriteOp,
+    },
+    optimizer::AnalyzerRule,
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionMode,
+        ExecutionPlan, Partitioning, PlanProperties,
+    },
+    physical_planner::{DefaultPhysicalPlanner, ExtensionPlanner, PhysicalPlanner},
+    prelude::{DataFrame, Expr, SessionContext},
+    sql::{
+        parser::Statement as DFStatement,
+        sqlparser::ast::{
+            Assignment, MergeAction, MergeClause, MergeClauseKind, MergeInsertKind, Statement,
+            TableFactor,
+        },
+    },
+};
+use deltalake::{operations::merge::MergeBuilder, DeltaResult};
+
+use super::table::delta::UnityDeltaTable;
+
+/// Let `state` execute `DELETE` and `UPDATE` statements on Unity Delta
+/// tables.
+///
+/// The statements return the number of affected rows in a `count` column,
+/// like `INSERT INTO`. This replaces the query planner of `state`.
+///
+/// # Example
+/// ```no_run
+/// # async fn example(unity: datafusion_unity_catalog::Unity) -> datafusion::error::Result<()> {
+/// use std::sync::Arc;
+/// use datafusion::prelude::SessionContext;
+/// use datafusion_unity_catalog::enable_dml;
+///
+/// let mut ctx = SessionContext::new_with_state(enable_dml(SessionContext::new().state()));
+/// ctx.register_catalog_list(Arc::new(unity));
+/// ctx.sql("DELETE FROM unity.sales.orders WHERE amount = 0")
+///     .await?
+///     .show()
+///     .await?;
+/// # Ok(()) }
+/// ```
+pub fn enable_dml(state: SessionState) -> SessionState {
+    state
+        .add_analyzer_rule(Arc::new(DeltaDmlRule))
+        .with_query_planner(Arc::new(DeltaDmlPlanner))
+}
+
+/// Rewrites `DELETE` and `UPDATE` statements on Unity Delta tables into
+/// [`DeltaDml`] nodes.
+#[derive(Debug)]
+struct DeltaDmlRule;
+
+impl AnalyzerRule for DeltaDmlRule {
+    fn analyze(
+        &self,
+        plan: LogicalPlan,
+        _config: &ConfigOptions,
+    ) -> Result<LogicalPlan, DataFusionError> {
+        plan.transform_up(|plan| {
+            let LogicalPlan::Dml(dml) = &plan else {
+                return Ok(Transformed::no(plan));
+            };
+            Ok(match DeltaDml::try_new(dml) {
+                Some(node) => Transformed::yes(LogicalPlan::Extension(Extension {
+                    node: Arc::new(node),
+                })),
+                None => Transformed::no(plan),
+            })
+        })
+        .data()
+    }
+
+    fn name(&self) -> &str {
+        "unity_delta_dml"
+    }
+}
+
+/// A `DELETE` or `UPDATE` of a Unity Delta table.
+///
+/// The predicate and the assigned values are evaluated by `deltalake`
+/// against the columns of the table, so they are not exposed to the
+/// optimizer.
+#[derive(Clone)]
+struct DeltaDml {
+    table_name: TableReference,
+    table: Arc<dyn TableProvider>,
+    op: WriteOp,
+    predicate: Option<Expr>,
+    /// New values of the updated columns.
+    assignments: Vec<(String, Expr)>,
+    schema: DFSchemaRef,
+}
+
+impl DeltaDml {
+    /// The operation `dml` performs, if it deletes or updates the rows of a
+    /// Unity Delta table that match a predicate.
+    ///
+    /// DataFusion plans `DELETE` as a filtered scan of the table, and
+    /// `UPDATE` as a projection of the new row values on top of it.
+    fn try_new(dml: &DmlStatement) -> Option<Self> {
+        let mut input = dml.input.as_ref();
+        let mut assignments = Vec::new();
+        match (&dml.op, input) {
+            (WriteOp::Delete, _) => {}
+            (WriteOp::Update, LogicalPlan::Projection(projection)) => {
+                for expr in &projection.expr {
+                    let Expr::Alias(alias) = expr else {
+                        return None;
+                    };
+                    let unchanged = matches!(alias.expr.as_ref(), Expr::Column(column) if column.name == alias.name);
+                    if !unchanged {
+                        let value = unnormalize_col(alias.expr.as_ref().clone());
+                        assignments.push((alias.name.clone(), value));
+                    }
+                }
+                input = projection.input.as_ref();
+            }
+            _ => return None,
+        }
+
+        let mut predicates = Vec::new();
+        let scan = loop {
+            match input {
+                LogicalPlan::Filter(filter) => {
+                    predicates.push(unnormalize_col(filter.predicate.clone()));
+                    input = filter.input.as_ref();
+                }
+                LogicalPlan::SubqueryAlias(alias) => input = alias.input.as_ref(),
+                LogicalPlan::TableScan(scan) => break scan,
+                _ => return None,
+            }
+        };
+        let table = source_as_provider(&scan.source).ok()?;
+        table.as_any().downcast_ref::<UnityDeltaTable>()?;
+
+        Some(Self {
+            table_name: dml.table_name.clone(),
+            table,
+            op: dml.op.clone(),
+            predicate: conjunction(predicates),
+            assignments,
+            schema: dml.output_schema.clone(),
+        })
+    }
+
+    /// Run the operation, returning the number of affected rows.
+    async fn execute(self, state: SessionState) -> Result<usize, DataFusionError> {
+        let table = self
+            .table
+            .as_any()
+            .downcast_ref::<UnityDeltaTable>()
+            .expect("only Unity Delta tables are planned");
+        let rows = match self.op {
+            WriteOp::Delete => table
+                .delete(state, self.predicate)
+                .await?
+                .num_deleted_rows
+                .unwrap_or_default(),
+            _ => {
+                table
+                    .update(state, self.assignments, self.predicate)
+                    .await?
+                    .num_updated_rows
+            }
+        };
+        Ok(rows)
+    }
+}
+
+impl fmt::Debug for DeltaDml {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeltaDml")
+            .field("table_name", &self.table_name)
+            .field("op", &self.op.to_string())
+            .field("predicate", &self.predicate)
+            .field("assignments", &self.assignments)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for DeltaDml {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_name == other.table_name
+            && self.op == other.op
+            && self.predicate == other.predicate
+            && self.assignments == other.assignments
+    }
+}
+
+impl Eq for DeltaDml {}
+
+impl Hash for DeltaDml {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table_name.hash(state);
+        self.op.hash(state);
+        self.predicate.hash(state);
+        self.assignments.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for DeltaDml {
+    fn name(&self) -> &str {
+        "DeltaDml"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeltaDml: op={} table={}", self.op, self.table_name)?;
+        for (column, value) in &self.assignments {
+            write!(f, " {}={}", column, value)?;
+        }
+        if let Some(predicate) = &self.predicate {
+            write!(f, " predicate={}", predicate)?;
+        }
+        Ok(())
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        _inputs: Vec<LogicalPlan>,
+    ) -> Result<Self, DataFusionError> {
+        Ok(self.clone())
+    }
+}
+
+/// Plans [`DeltaDml`] nodes, and everything else like DataFusion does.
+#[derive(Debug)]
+struct DeltaDmlPlanner;
+
+#[async_trait]
+impl QueryPlanner for DeltaDmlPlanner {
+    async fn create_physical_plan(
+        &self,
+        logical_plan: &LogicalPlan,
+        session_state: &SessionState,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(DeltaDmlPlanner)])
+            .create_physical_plan(logical_plan, session_state)
+            .await
+    }
+}
+
+#[async_trait]
+impl ExtensionPlanner for DeltaDmlPlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>, DataFusionError> {
+        Ok(node
+            .as_any()
+            .downcast_ref::<DeltaDml>()
+            .map(|dml| Arc::new(DeltaDmlExec::new(dml.clone(), session_state.clone())) as _))
+    }
+}
+
+/// Runs a [`DeltaDml`] node and returns the number of affected rows.
+struct DeltaDmlExec {
+    dml: DeltaDml,
+    state: SessionState,
+    properties: PlanProperties,
+}
+
+impl DeltaDmlExec {
+    fn new(dml: DeltaDml, state: SessionState) -> Self {
+        let schema: SchemaRef = dml.schema.inner().clone();
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(schema),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+        Self {
+            dml,
+            state,
+            properties,
+        }
+    }
+}
+
+impl fmt::Debug for DeltaDmlExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeltaDmlExec")
+            .field("dml", &self.dml)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DisplayAs for DeltaDmlExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DeltaDmlExec: op={} table={}",
+            self.dml.op, self.dml.table_name
+        )
+    }
+}
+
+impl ExecutionPlan for DeltaDmlExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        if !children.is_empty() {
+            return Err(DataFusionError::Internal(
+                "DeltaDmlExec has no children".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream, DataFusionError> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "DeltaDmlExec has no partition {}",
+                partition
+            )));
+        }
+        let schema = self.schema();
+        let dml = self.dml.clone();
+        let state = self.state.clone();
+        let count = {
+            let schema = schema.clone();
+            async move {
+                let rows = dml.execute(state).await?;
+                let count = Arc::new(UInt64Array::from(vec![rows as u64]));
+                Ok(RecordBatch::try_new(schema, vec![count])?)
+            }
+        };
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(count),
+        )))
+    }
+}
+
+/// Run the `MERGE INTO` statement `sql` against a Unity Delta table in
+/// `ctx`.
+///
+/// Returns a single row with the number of inserted, updated and deleted
+/// rows. Expressions are evaluated against the target table, so columns are
+/// referenced by their name or by the aliases of the target and source.
+///
+/// # Example
+/// ```no_run
+/// # async fn example(ctx: datafusion::prelude::SessionContext) -> datafusion::error::Result<()> {
+/// let merged = datafusion_unity_catalog::execute_merge(
+///     &ctx,
+///     "MERGE INTO unity.sales.orders AS t USING updates AS s ON t.id = s.id \
+///      WHEN MATCHED THEN UPDATE SET amount = s.amount \
+///      WHEN NOT MATCHED THEN INSERT (id, amount) VALUES (s.id, s.amount)",
+/// )
+/// .await?;
+/// merged.show().await?;
+/// # Ok(()) }
+/// ```
+pub async fn execute_merge(ctx: &SessionContext, sql: &str) -> Result<DataFrame, DataFusionError> {
+    let state = ctx.state();
+    let dialect = state.config().options().sql_parser.dialect.clone();
+    let statement = match state.sql_to_statement(sql, &dialect)? {
+        DFStatement::Statement(statement) => *statement,
+        _ => return Err(not_a_merge()),
+    };
+    let Statement::Merge {
+        table,
+        source,
+        on,
+        clauses,
+        ..
+    } = statement
+    else {
+        return Err(not_a_merge());
+    };
+    merge_into(ctx, table, source, on.to_string(), clauses).await
+}
+
+fn not_a_merge() -> DataFusionError {
+    DataFusionError::Plan("expected a MERGE INTO statement".to_string())
+}
+
+async fn merge_into(
+    ctx: &SessionContext,
+    table: TableFactor,
+    source: TableFactor,
+    predicate: String,
+    clauses: Vec<MergeClause>,
+) -> Result<DataFrame, DataFusionError> {
+    let TableFactor::Table { name, alias, .. } = &table else {
+        return not_implemented("MERGE INTO anything but a table");
+    };
+    let target_alias = alias
+        .as_ref()
+        .map(|alias| alias.name.value.clone())
+        .unwrap_or_else(|| name.0.last().map(|i| i.value.clone()).unwrap_or_default());
+    let provider = ctx.table_provider(name.to_string()).await?;
+    let target = as_delta(&provider, &name.to_string())?;
+
+    let (source, source_alias) = match source {
+        TableFactor::Table { name, alias, .. } => {
+            let alias = alias
+                .map(|alias| alias.name.value)
+                .unwrap_or_else(|| name.0.last().map(|i| i.value.clone()).unwrap_or_default());
+            (ctx.table(name.to_string()).await?, alias)
+        }
+        TableFactor::Derived {
+            subquery,
+            alias: Some(alias),
+            ..
+        } => (ctx.sql(&subquery.to_string()).await?, alias.name.value),
+        _ => return not_implemented("MERGE USING anything but a table or aliased subquery"),
+    };
+
+    let clauses = |mut builder: MergeBuilder| -> DeltaResult<MergeBuilder> {
+        builder = builder
+            .with_target_alias(target_alias)
+            .with_source_alias(source_alias);
+        for clause in clauses {
+            builder = merge_clause(builder, clause, target)?;
+        }
+        Ok(builder)
+    };
+    let metrics = target
+        .merge(ctx.state(), source, predicate, clauses)
+        .await?;
+    affected_rows(
+        ctx,
+        &[
+            ("num_inserted_rows", metrics.num_target_rows_inserted),
+            ("num_updated_rows", metrics.num_target_rows_updated),
+            ("num_deleted_rows", metrics.num_target_rows_deleted),
+        ],
+    )
+}
+
+/// Add a `WHEN .. THEN ..` clause to a merge.
+fn merge_clause(
+    builder: MergeBuilder,
+    clause: MergeClause,
+    target: &UnityDeltaTable,
+) -> DeltaResult<MergeBuilder> {
+    let predicate = clause.predicate.map(|e| e.to_string());
+    match (clause.clause_kind, clause.action) {
+        (MergeClauseKind::Matched, MergeAction::Update { assignments }) => builder
+            .when_matched_update(|mut update| {
+                for (column, value) in assignments.into_iter().map(assignment) {
+                    update = update.update(column, value);
+                }
+                match predicate {
+                    Some(predicate) => update.predicate(predicate),
+                    None => update,
+                }
+            }),
+        (MergeClauseKind::Matched, MergeAction::Delete) => {
+            builder.when_matched_delete(|delete| match predicate {
+                Some(predicate) => delete.predicate(predicate),
+                None => delete,
+            })
+        }
+        (
+            MergeClauseKind::NotMatched | MergeClauseKind::NotMatchedByTarget,
+            MergeAction::Insert(insert),
+        ) => {
+            let MergeInsertKind::Values(values) = insert.kind else {
+                return Err(unsupported_merge("INSERT ROW"));
+            };
+            let [row] = &values.rows[..] else {
+                return Err(unsupported_merge("INSERT with more than one row"));
+            };
+            let columns: Vec<String> = if insert.columns.is_empty() {
+                target
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| field.name().clone())
+                    .collect()
+            } else {
+                insert.columns.into_iter().map(|c| c.value).collect()
+            };
+            if columns.len() != row.len() {
+                return Err(unsupported_merge(
+                    "INSERT with a different number of columns and values",
+                ));
+            }
+            builder.when_not_matched_insert(|mut insert| {
+                for (column, value) in columns.into_iter().zip(row) {
+                    insert = insert.set(column, value.to_string());
+                }
+                match predicate {
+                    Some(predicate) => insert.predicate(predicate),
+                    None => insert,
+                }
+            })
+        }
+        (MergeClauseKind::NotMatchedBySource, MergeAction::Update { assignments }) => builder
+            .when_not_matched_by_source_update(|mut update| {
+                for (column, value) in assignments.into_iter().map(assignment) {
+                    update = update.update(column, value);
+                }
+                match predicate {
+                    Some(predicate) => update.predicate(predicate),
+                    None => update,
+                }
+            }),
+        (MergeClauseKind::NotMatchedBySource, MergeAction::Delete) => builder
+            .when_not_matched_by_source_delete(|delete| match predicate {
+                Some(predicate) => delete.predicate(predicate),
+                None => delete,
+            }),
+        (kind, action) => Err(unsupported_merge(&format!("WHEN {} THEN {}", kind, action))),
+    }
+}
+
+/// The column and value of `column = value`, ignoring any table qualifier.
+fn assignment(assignment: Assignment) -> (String, String) {
+    let column = assignment
+        .id
+        .last()
+        .map(|ident| ident.value.clone())
+        .unwrap_or_default();
+    (column, assignment.value.to_string())
+}
+
+/// The Unity Delta table behind `provider`, which was looked up as `name`.
+fn as_delta<'a>(
+    provider: &'a Arc<dyn TableProvider>,
+    name: &str,
+) -> Result<&'a UnityDeltaTable, DataFusionError> {
+    match provider.as_any().downcast_ref::<UnityDeltaTable>() {
+        Some(table) => Ok(table),
+        None => not_implemented(&format!(
+            "DML on '{}', which is not a Unity Delta table,",
+            name
+        )),
+    }
+}
+
+/// A single row with the given counts.
+fn affected_rows(
+    ctx: &SessionContext,
+    counts: &[(&str, usize)],
+) -> Result<DataFrame, DataFusionError> {
+    let schema = Schema::new(
+        counts
+            .iter()
+            .map(|(name, _)| Field::new(*name, DataType::UInt64, false))
+            .collect::<Vec<_>>(),
+    );
+    let columns = counts
+        .iter()
+        .map(|(_, count)| Arc::new(UInt64Array::from(vec![*count as u64])) as ArrayRef)
+        .collect();
+    ctx.read_batch(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn unsupported_merge(what: &str) -> deltalake::DeltaTableError {
+    deltalake::DeltaTableError::Generic(format!("MERGE {} is not supported", what))
+}
+
+fn not_implemented<T>(what: &str) -> Result<T, DataFusionError> {
+    Err(DataFusionError::NotImplemented(format!(
+        "{} is not supported",
+        what
+    )))
+}
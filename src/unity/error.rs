@@ -1,24 +1,193 @@
 use std::fmt;
 
-use crate::client::error::ClientError;
+use datafusion::error::DataFusionError;
+use deltalake::{DeltaTableError, ObjectStoreError};
+
+use crate::client::error::{ApiError, ClientError};
 
 /// Unity error
 #[derive(Debug)]
-pub struct UnityError {
+#[non_exhaustive]
+pub enum UnityError {
+    /// The catalog, schema or table does not exist.
+    NotFound {
+        /// Full name of the object, e.g. `main.default.sales`.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The caller is not allowed to access the object.
+    PermissionDenied {
+        /// Full name of the object.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The request carried no or invalid credentials.
+    Unauthenticated {
+        /// Description of the failure.
+        message: String,
+    },
+    /// The server rejected the request for another reason.
+    Api {
+        /// Full name of the object.
+        name: String,
+        /// Error reported by the server.
+        error: ApiError,
+    },
+    /// The server returned a response that could not be interpreted.
+    InvalidResponse {
+        /// Full name of the object.
+        name: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The server could not be reached.
+    Transport {
+        /// Description of the failure.
+        message: String,
+    },
+    /// The Delta table could not be loaded.
+    Delta {
+        /// Full name of the table.
+        name: String,
+        /// Underlying Delta error.
+        source: DeltaTableError,
+    },
+    /// The schema registered in Unity Catalog does not match the Delta log.
+    SchemaMismatch {
+        /// Full name of the table.
+        name: String,
+        /// Description of the difference.
+        message: String,
+    },
+    /// The table storage could not be accessed.
+    Storage {
+        /// Full name of the table.
+        name: String,
+        /// Underlying object store error.
+        source: ObjectStoreError,
+    },
+    /// The table uses a format or column type that is not supported.
+    Unsupported {
+        /// Full name of the table.
+        name: String,
+        /// Description of the unsupported feature.
+        message: String,
+    },
+    /// The catalog is misconfigured.
+    Config {
+        /// Description of the problem.
+        message: String,
+    },
+}
 
+impl UnityError {
+    pub(crate) fn delta(name: impl Into<String>, source: DeltaTableError) -> UnityError {
+        let name = name.into();
+        match source {
+            DeltaTableError::ObjectStore { source } => UnityError::Storage { name, source },
+            source => UnityError::Delta { name, source },
+        }
+    }
 }
 
 impl fmt::Display for UnityError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "UnityError")
+        match self {
+            Self::NotFound { name, error } => {
+                write!(f, "'{}' not found in Unity Catalog ({})", name, error)
+            }
+            Self::PermissionDenied { name, error } => {
+                write!(f, "Permission denied on '{}' ({})", name, error)
+            }
+            Self::Unauthenticated { message } => {
+                write!(f, "Unauthenticated request to Unity Catalog: {}", message)
+            }
+            Self::Api { name, error } => {
+                write!(f, "Unity Catalog request for '{}' failed ({})", name, error)
+            }
+            Self::InvalidResponse { name, message } => write!(
+                f,
+                "Invalid Unity Catalog response for '{}': {}",
+                name, message
+            ),
+            Self::Transport { message } => {
+                write!(f, "Cannot reach Unity Catalog: {}", message)
+            }
+            Self::Delta { name, source } => {
+                write!(f, "Cannot load Delta table '{}': {}", name, source)
+            }
+            Self::SchemaMismatch { name, message } => write!(
+                f,
+                "Schema of table '{}' in Unity Catalog does not match its Delta log: {}",
+                name, message
+            ),
+            Self::Storage { name, source } => {
+                write!(f, "Cannot access storage of table '{}': {}", name, source)
+            }
+            Self::Unsupported { name, message } => {
+                write!(f, "Unsupported table '{}': {}", name, message)
+            }
+            Self::Config { message } => write!(f, "Invalid configuration: {}", message),
+        }
     }
 }
 
-impl std::error::Error for UnityError {}
+impl std::error::Error for UnityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Delta { source, .. } => Some(source),
+            Self::Storage { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 impl From<ClientError> for UnityError {
-    fn from(_: ClientError) -> Self {
-        UnityError {}
+    fn from(e: ClientError) -> Self {
+        match e {
+            ClientError::NotFound { name, error } => UnityError::NotFound { name, error },
+            ClientError::PermissionDenied { name, error } => {
+                UnityError::PermissionDenied { name, error }
+            }
+            ClientError::Unauthenticated { message } => UnityError::Unauthenticated { message },
+            ClientError::Api { name, error } => UnityError::Api { name, error },
+            ClientError::InvalidResponse { name, message } => {
+                UnityError::InvalidResponse { name, message }
+            }
+            ClientError::Transport { message } => UnityError::Transport { message },
+            ClientError::Config { message } => UnityError::Config { message },
+        }
+    }
+}
+
+impl From<UnityError> for DataFusionError {
+    fn from(e: UnityError) -> Self {
+        DataFusionError::External(Box::new(e))
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_into_datafusion_error() {
+        let error = UnityError::from(ClientError::NotFound {
+            name: "main.default.sales".to_string(),
+            error: ApiError::from_body(
+                404,
+                r#"{"error_code":"TABLE_DOES_NOT_EXIST","message":"Table not found"}"#,
+            ),
+        });
+
+        let error = DataFusionError::from(error);
+        assert!(matches!(error, DataFusionError::External(_)));
+        assert_eq!(
+            error.to_string(),
+            "External error: 'main.default.sales' not found in Unity Catalog \
+             (HTTP 404 TABLE_DOES_NOT_EXIST: Table not found)"
+        );
+    }
+}
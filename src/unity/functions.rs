@@ -0,0 +1,271 @@
+//! Table functions that read Unity tables, registered with
+//! [`Unity::register_table_functions`](super::Unity::register_table_functions).
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use datafusion::{
+    common::{plan_err, ResolvedTableReference, ScalarValue, TableReference},
+    datasource::{function::TableFunctionImpl, MemTable, TableProvider},
+    error::{DataFusionError, Result},
+    logical_expr::Expr,
+    prelude::SessionContext,
+};
+
+use super::{
+    runtime::block_on,
+    table::{
+        changes::TableChanges,
+        delta::UnityDeltaTable,
+        history::{detail, history},
+        version::TableVersion,
+    },
+    Metastore,
+};
+use crate::client::Table;
+
+pub(crate) fn register(metastore: Arc<Metastore>, ctx: &SessionContext) {
+    let tables = TableResolver::new(metastore, ctx);
+    ctx.register_udtf(
+        "unity_table_version",
+        Arc::new(TableAtVersion {
+            tables: tables.clone(),
+        }),
+    );
+    ctx.register_udtf(
+        "unity_table_as_of",
+        Arc::new(TableAsOf {
+            tables: tables.clone(),
+        }),
+    );
+    ctx.register_udtf(
+        "table_changes",
+        Arc::new(TableChangesFunction {
+            tables: tables.clone(),
+        }),
+    );
+    ctx.register_udtf(
+        "describe_history",
+        Arc::new(DescribeHistory {
+            tables: tables.clone(),
+        }),
+    );
+    ctx.register_udtf("describe_detail", Arc::new(DescribeDetail { tables }));
+}
+
+/// Resolves table names passed to table functions against the default
+/// catalog and schema of the session the functions are registered with.
+#[derive(Clone)]
+struct TableResolver {
+    metastore: Arc<Metastore>,
+    default_catalog: String,
+    default_schema: String,
+}
+
+impl TableResolver {
+    fn new(metastore: Arc<Metastore>, ctx: &SessionContext) -> Self {
+        let state = ctx.state();
+        let defaults = &state.config().options().catalog;
+        Self {
+            metastore,
+            default_catalog: defaults.default_catalog.clone(),
+            default_schema: defaults.default_schema.clone(),
+        }
+    }
+
+    fn resolve(&self, name: &str) -> ResolvedTableReference {
+        TableReference::parse_str(name).resolve(&self.default_catalog, &self.default_schema)
+    }
+
+    /// Load Unity Delta table `name`, as of `version` if one is given.
+    async fn delta_table(
+        &self,
+        name: &str,
+        version: Option<TableVersion>,
+    ) -> Result<(Table, UnityDeltaTable)> {
+        let name = self.resolve(name);
+        let table = match self
+            .metastore
+            .find_schema(&name.catalog, &name.schema)
+            .await?
+        {
+            Some(schema) => schema.delta_table(&name.table, version).await?,
+            None => None,
+        };
+        match table {
+            Some(table) => Ok(table),
+            None => plan_err!("table '{}' not found in Unity Catalog", name),
+        }
+    }
+
+    fn table_at(&self, name: &str, version: TableVersion) -> Result<Arc<dyn TableProvider>> {
+        let (_, table) = block_on(self.delta_table(name, Some(version)))??;
+        Ok(Arc::new(table))
+    }
+}
+
+/// `unity_table_version(name, version)`
+struct TableAtVersion {
+    tables: TableResolver,
+}
+
+impl TableFunctionImpl for TableAtVersion {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [name, version] = args else {
+            return plan_err!("unity_table_version expects a table name and a version");
+        };
+        self.tables.table_at(
+            &name_arg("unity_table_version", name)?,
+            TableVersion::Version(version_arg("unity_table_version", version)?),
+        )
+    }
+}
+
+/// `unity_table_as_of(name, timestamp)`
+struct TableAsOf {
+    tables: TableResolver,
+}
+
+impl TableFunctionImpl for TableAsOf {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [name, timestamp] = args else {
+            return plan_err!("unity_table_as_of expects a table name and a timestamp");
+        };
+        let timestamp = timestamp_arg("unity_table_as_of", timestamp)?;
+        self.tables.table_at(
+            &name_arg("unity_table_as_of", name)?,
+            TableVersion::Timestamp(timestamp),
+        )
+    }
+}
+
+/// `table_changes(name, start_version[, end_version])`
+struct TableChangesFunction {
+    tables: TableResolver,
+}
+
+impl TableFunctionImpl for TableChangesFunction {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let (name, start, end) = match args {
+            [name, start] => (name, start, None),
+            [name, start, end] => (name, start, Some(version_arg("table_changes", end)?)),
+            _ => {
+                return plan_err!(
+                "table_changes expects a table name, a start version and an optional end version"
+            )
+            }
+        };
+        let name = name_arg("table_changes", name)?;
+        let start = version_arg("table_changes", start)?;
+
+        let changes = block_on(async {
+            let (table, provider) = self.tables.delta_table(&name, None).await?;
+            let snapshot = provider.snapshot().await?;
+            let changes =
+                TableChanges::try_new(&table.full_name(), snapshot, table.properties(), start, end)
+                    .await?;
+            Ok::<_, DataFusionError>(changes)
+        })??;
+        Ok(Arc::new(changes))
+    }
+}
+
+/// `describe_history(name[, limit])`
+struct DescribeHistory {
+    tables: TableResolver,
+}
+
+impl TableFunctionImpl for DescribeHistory {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let (name, limit) = match args {
+            [name] => (name, None),
+            [name, Expr::Literal(ScalarValue::Int64(Some(limit)))] if *limit >= 0 => {
+                (name, Some(*limit as usize))
+            }
+            _ => return plan_err!("describe_history expects a table name and an optional limit"),
+        };
+        let name = name_arg("describe_history", name)?;
+
+        let batch = block_on(async {
+            let (table, provider) = self.tables.delta_table(&name, None).await?;
+            let snapshot = provider.snapshot().await?;
+            Ok::<_, DataFusionError>(history(&table.full_name(), &snapshot, limit).await?)
+        })??;
+        Ok(Arc::new(MemTable::try_new(
+            batch.schema(),
+            vec![vec![batch]],
+        )?))
+    }
+}
+
+/// `describe_detail(name)`
+struct DescribeDetail {
+    tables: TableResolver,
+}
+
+impl TableFunctionImpl for DescribeDetail {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [name] = args else {
+            return plan_err!("describe_detail expects a table name");
+        };
+        let name = name_arg("describe_detail", name)?;
+
+        let batch = block_on(async {
+            let (table, provider) = self.tables.delta_table(&name, None).await?;
+            let snapshot = provider.snapshot().await?;
+            Ok::<_, DataFusionError>(detail(&table.full_name(), &snapshot).await?)
+        })??;
+        Ok(Arc::new(MemTable::try_new(
+            batch.schema(),
+            vec![vec![batch]],
+        )?))
+    }
+}
+
+fn name_arg(function: &str, arg: &Expr) -> Result<String> {
+    match arg {
+        Expr::Literal(ScalarValue::Utf8(Some(name))) => Ok(name.clone()),
+        _ => plan_err!("{} expects a table name as first argument", function),
+    }
+}
+
+fn version_arg(function: &str, arg: &Expr) -> Result<i64> {
+    match arg {
+        Expr::Literal(ScalarValue::Int64(Some(version))) if *version >= 0 => Ok(*version),
+        _ => plan_err!("{} expects non-negative table versions", function),
+    }
+}
+
+/// A timestamp given as an RFC 3339 string or a timestamp literal, which is
+/// taken to be in UTC unless it has a time zone.
+fn timestamp_arg(function: &str, arg: &Expr) -> Result<DateTime<Utc>> {
+    let timestamp = match arg {
+        Expr::Literal(ScalarValue::Utf8(Some(timestamp))) => {
+            return DateTime::parse_from_rfc3339(timestamp)
+                .map(|timestamp| timestamp.to_utc())
+                .map_err(|e| {
+                    DataFusionError::Plan(format!(
+                        "{} cannot parse timestamp '{}': {}",
+                        function, timestamp, e
+                    ))
+                });
+        }
+        Expr::Literal(ScalarValue::TimestampSecond(Some(ts), _)) => {
+            DateTime::from_timestamp(*ts, 0)
+        }
+        Expr::Literal(ScalarValue::TimestampMillisecond(Some(ts), _)) => {
+            DateTime::from_timestamp_millis(*ts)
+        }
+        Expr::Literal(ScalarValue::TimestampMicrosecond(Some(ts), _)) => {
+            DateTime::from_timestamp_micros(*ts)
+        }
+        Expr::Literal(ScalarValue::TimestampNanosecond(Some(ts), _)) => {
+            Some(DateTime::from_timestamp_nanos(*ts))
+        }
+        _ => return plan_err!("{} expects a timestamp as second argument", function),
+    };
+    match timestamp {
+        Some(timestamp) => Ok(timestamp),
+        None => plan_err!("{} got a timestamp out of range", function),
+    }
+}
@@ -1,22 +1,59 @@
 //! Unity Calalog implementation for DataFusion
 
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, Weak},
+    time::Duration,
+};
 
-use datafusion::catalog::{CatalogProvider, CatalogProviderList};
+use dashmap::DashMap;
+use datafusion::{
+    catalog::{CatalogProvider, CatalogProviderList},
+    prelude::SessionContext,
+};
+use tokio::task::JoinHandle;
 
-use crate::{client::UnityClient, unity::catalog::Catalog};
+use crate::{
+    client::{auth::CredentialProvider, UnityClient},
+    unity::{catalog::Catalog, schema::UnitySchema},
+};
 
-use self::error::UnityError;
+use self::{
+    builder::UnityBuilder,
+    cache::MetadataCache,
+    error::UnityError,
+    options::{CatalogRegistration, LoadingMode, UnityOptions},
+    runtime::{block_on, ensure_blocking_allowed},
+    table::cache::DeltaTableCache,
+};
 
+pub mod builder;
+mod cache;
 mod catalog;
+pub(crate) mod dml;
 pub mod error;
+mod functions;
+pub mod options;
+mod runtime;
 mod schema;
 mod table;
 
 /// Unity Catalog
 pub struct Unity {
+    metastore: Arc<Metastore>,
+}
+
+/// State shared by a [`Unity`] instance and the views in it, which are planned
+/// against the same catalogs.
+struct Metastore {
     client: Arc<UnityClient>,
-    catalogs: HashMap<String, Arc<dyn CatalogProvider>>,
+    options: Arc<UnityOptions>,
+    catalogs: MetadataCache<Arc<Catalog>>,
+    /// Catalogs registered with DataFusion that are served from memory.
+    local_catalogs: DashMap<String, Arc<dyn CatalogProvider>>,
+    tables: Arc<DeltaTableCache>,
+    this: Weak<Metastore>,
 }
 
 impl Unity {
@@ -36,35 +73,347 @@ impl Unity {
     /// # Ok(()) }
     /// ```
     pub async fn try_new(endpoint: &str) -> Result<Self, UnityError> {
-        let client = Arc::new(UnityClient::new(endpoint));
-        let catalogs = HashMap::new();
+        Self::try_new_with_options(endpoint, UnityOptions::default()).await
+    }
 
-        let mut unity = Self { client, catalogs };
-        unity.fetch().await?;
+    /// Initialize a new [`Unity`] instance that authenticates every request
+    /// with the given [`CredentialProvider`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # async {
+    /// # use datafusion_unity_catalog::UnityError;
+    /// use std::sync::Arc;
+    /// use datafusion_unity_catalog::{OAuthClientCredentials, Unity};
+    ///
+    /// let credentials = OAuthClientCredentials::new(
+    ///     "https://example.cloud.databricks.com/oidc/v1/token",
+    ///     "client-id",
+    ///     "client-secret",
+    /// )
+    /// .with_scope("all-apis");
+    /// let unity_catalog = Unity::try_new_with_credentials(
+    ///     "https://example.cloud.databricks.com/api/2.1/unity-catalog/",
+    ///     Arc::new(credentials),
+    /// )
+    /// .await?;
+    /// # Ok::<(), UnityError>(()) };
+    /// # Ok(()) }
+    /// ```
+    pub async fn try_new_with_credentials(
+        endpoint: &str,
+        credentials: Arc<dyn CredentialProvider>,
+    ) -> Result<Self, UnityError> {
+        let options = UnityOptions::default()
+            .with_client_builder(|client| client.with_credentials(credentials));
+        Self::try_new_with_options(endpoint, options).await
+    }
+
+    /// Initialize a new [`Unity`] instance with the given endpoint and [`UnityOptions`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # async {
+    /// # use datafusion_unity_catalog::UnityError;
+    /// use datafusion_unity_catalog::{ErrorPolicy, Unity, UnityOptions};
+    ///
+    /// // skip catalogs, schemas and tables that cannot be loaded
+    /// let options = UnityOptions::new().with_error_policy(ErrorPolicy::SkipAndLog);
+    /// let unity_catalog =
+    ///     Unity::try_new_with_options("http://localhost:8080/api/2.1/unity-catalog/", options)
+    ///         .await?;
+    /// # Ok::<(), UnityError>(()) };
+    /// # Ok(()) }
+    /// ```
+    pub async fn try_new_with_options(
+        endpoint: &str,
+        options: UnityOptions,
+    ) -> Result<Self, UnityError> {
+        let client = options.client.clone().with_endpoint(endpoint).build()?;
+        Self::try_new_with_client(client, options).await
+    }
 
-        Ok(unity)
+    /// Start configuring a [`Unity`] instance with a [`UnityBuilder`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # async {
+    /// # use datafusion_unity_catalog::UnityError;
+    /// use datafusion_unity_catalog::{LoadingMode, Unity};
+    ///
+    /// // read UC_ENDPOINT and UC_TOKEN, and only expose catalog `main`
+    /// let unity_catalog = Unity::builder()
+    ///     .with_env()
+    ///     .with_options(|options| {
+    ///         options
+    ///             .with_included_catalogs(["main"])
+    ///             .with_loading_mode(LoadingMode::Lazy)
+    ///     })
+    ///     .build()
+    ///     .await?;
+    /// # Ok::<(), UnityError>(()) };
+    /// # Ok(()) }
+    /// ```
+    pub fn builder() -> UnityBuilder {
+        UnityBuilder::new()
     }
 
-    async fn _try_new_with_client(client: UnityClient) -> Result<Self, UnityError> {
+    pub(crate) async fn try_new_with_client(
+        client: UnityClient,
+        options: UnityOptions,
+    ) -> Result<Self, UnityError> {
+        // lazily loaded and expired metadata is fetched from synchronous
+        // DataFusion callbacks, which cannot block a current-thread runtime
+        let on_demand = options.loading_mode == LoadingMode::Lazy
+            || options.catalog_ttl.is_some()
+            || options.schema_ttl.is_some()
+            || options.table_ttl.is_some();
+        if on_demand {
+            ensure_blocking_allowed()?;
+        }
+
         let client = Arc::new(client);
-        let catalogs = HashMap::new();
+        let options = Arc::new(options);
+        let metastore = Arc::new_cyclic(|this| Metastore {
+            client,
+            catalogs: MetadataCache::new(options.catalog_ttl),
+            local_catalogs: DashMap::new(),
+            tables: Arc::new(DeltaTableCache::new(
+                options.table_staleness,
+                options.storage_options.clone(),
+            )),
+            options,
+            this: this.clone(),
+        });
+        metastore.fetch().await?;
 
-        let mut unity = Self { client, catalogs };
-        unity.fetch().await?;
+        Ok(Self { metastore })
+    }
+
+    /// Reload all catalog, schema and table metadata that has been loaded so far.
+    ///
+    /// Catalogs, schemas and tables created since the last load become
+    /// visible and dropped ones disappear.
+    pub async fn refresh(&self) -> Result<(), UnityError> {
+        self.metastore.refresh().await
+    }
+
+    /// Create catalog `name` in Unity Catalog.
+    pub async fn create_catalog(
+        &self,
+        name: &str,
+        comment: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<(), UnityError> {
+        self.metastore
+            .create_catalog(name, comment, properties)
+            .await
+            .map(|_| ())
+    }
 
-        Ok(unity)
+    /// Register the table functions that read Unity tables with `ctx`.
+    ///
+    /// Tables are passed by name and resolved against the default catalog
+    /// and schema of `ctx`:
+    /// - `unity_table_version('main.default.sales', 42)` reads version 42 of
+    ///   a Delta table,
+    /// - `unity_table_as_of('main.default.sales', '2024-01-31T12:00:00Z')`
+    ///   reads the version that was current at the given time,
+    /// - `table_changes('main.default.sales', 3[, 5])` reads the change data
+    ///   feed from version 3 up to version 5 or the latest version, which
+    ///   requires `delta.enableChangeDataFeed` to be set on the table,
+    /// - `describe_history('main.default.sales'[, 10])` lists the latest
+    ///   commits of a Delta table with their operations and metrics,
+    /// - `describe_detail('main.default.sales')` describes the current
+    ///   snapshot: its location, files, size, partitioning, properties and
+    ///   protocol versions.
+    ///
+    /// Like in Databricks, the columns returned by `describe_history` and
+    /// `describe_detail` are camel-cased and need to be quoted in SQL, e.g.
+    /// `"sizeInBytes"`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # async {
+    /// use std::sync::Arc;
+    /// use datafusion::prelude::*;
+    /// use datafusion_unity_catalog::Unity;
+    ///
+    /// let unity = Arc::new(Unity::try_new("http://localhost:8080/api/2.1/unity-catalog/").await?);
+    /// let mut ctx = SessionContext::new();
+    /// unity.register_table_functions(&ctx);
+    /// ctx.register_catalog_list(unity);
+    ///
+    /// ctx.sql("SELECT * FROM unity_table_version('unity.default.sales', 42)")
+    ///     .await?
+    ///     .show()
+    ///     .await?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(()) };
+    /// # Ok(()) }
+    /// ```
+    pub fn register_table_functions(&self, ctx: &SessionContext) {
+        functions::register(self.metastore.clone(), ctx);
     }
 
-    async fn fetch(&mut self) -> Result<(), UnityError> {
+    /// Reload the schemas of catalog `name` and the tables of those schemas.
+    pub async fn refresh_catalog(&self, name: &str) -> Result<(), UnityError> {
+        match self.metastore.find_catalog(name).await? {
+            Some(catalog) => catalog.refresh().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Reload the tables of schema `catalog_name.schema_name`.
+    pub async fn refresh_schema(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+    ) -> Result<(), UnityError> {
+        match self.metastore.find_catalog(catalog_name).await? {
+            Some(catalog) => catalog.refresh_schema(schema_name).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Periodically [`refresh`](Self::refresh) this instance in the background.
+    ///
+    /// The task stops once the last reference to the [`Unity`] instance is dropped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # async {
+    /// # use datafusion_unity_catalog::UnityError;
+    /// use std::{sync::Arc, time::Duration};
+    /// use datafusion::prelude::*;
+    /// use datafusion_unity_catalog::Unity;
+    ///
+    /// let unity = Arc::new(Unity::try_new("http://localhost:8080/api/2.1/unity-catalog/").await?);
+    /// unity.start_background_refresh(Duration::from_secs(300));
+    ///
+    /// let mut ctx = SessionContext::new();
+    /// ctx.register_catalog_list(unity);
+    /// # Ok::<(), UnityError>(()) };
+    /// # Ok(()) }
+    /// ```
+    pub fn start_background_refresh(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let unity = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(unity) = unity.upgrade() else {
+                    break;
+                };
+                if let Err(e) = unity.refresh().await {
+                    tracing::warn!("background refresh of Unity metadata failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+impl Metastore {
+    async fn refresh(&self) -> Result<(), UnityError> {
+        self.fetch().await?;
+        for catalog in self.catalogs.values() {
+            let result = catalog.refresh().await;
+            self.options.error_policy.apply(catalog.name(), result)?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch(&self) -> Result<(), UnityError> {
         let catalogs = self.client.list_catalogs().await?;
+        let mut listing = Vec::new();
         for catalog in catalogs {
-            let provider = Catalog::try_new(self.client.clone(), catalog.name()).await?;
-            self.catalogs
-                .insert(catalog.name().to_owned(), Arc::new(provider));
+            if !self.options.includes_catalog(catalog.name()) {
+                continue;
+            }
+            let provider = match self.catalogs.get_stale(catalog.name()) {
+                Some(provider) => provider,
+                None => {
+                    let provider = Catalog::try_new(
+                        self.client.clone(),
+                        self.options.clone(),
+                        self.tables.clone(),
+                        self.this.clone(),
+                        catalog.name(),
+                    )
+                    .await;
+                    match self.options.error_policy.apply(catalog.name(), provider)? {
+                        Some(provider) => Arc::new(provider),
+                        None => continue,
+                    }
+                }
+            };
+            listing.push((catalog.name().to_owned(), provider));
         }
+        self.catalogs.replace(listing);
 
         Ok(())
     }
+
+    async fn create_catalog(
+        &self,
+        name: &str,
+        comment: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<Arc<Catalog>, UnityError> {
+        let catalog = self
+            .client
+            .create_catalog(name, comment, properties)
+            .await?;
+        let provider = Arc::new(
+            Catalog::try_new(
+                self.client.clone(),
+                self.options.clone(),
+                self.tables.clone(),
+                self.this.clone(),
+                catalog.name(),
+            )
+            .await?,
+        );
+        if self.options.includes_catalog(catalog.name()) {
+            self.catalogs
+                .insert(catalog.name().to_owned(), provider.clone());
+        }
+        Ok(provider)
+    }
+
+    /// Find catalog `name`, listing catalogs again if it is not known yet.
+    async fn find_catalog(&self, name: &str) -> Result<Option<Arc<Catalog>>, UnityError> {
+        if !self.catalogs.contains(name) {
+            self.fetch().await?;
+        }
+        Ok(self.catalogs.get_stale(name))
+    }
+
+    /// Find schema `catalog_name.schema_name` in a Unity catalog.
+    async fn find_schema(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+    ) -> Result<Option<Arc<UnitySchema>>, UnityError> {
+        match self.find_catalog(catalog_name).await? {
+            Some(catalog) => catalog.find_schema(schema_name).await,
+            None => Ok(None),
+        }
+    }
+
+    fn ensure_fresh(&self) {
+        if !self.catalogs.is_fresh() {
+            if let Err(e) = block_on(self.fetch()).and_then(|r| r) {
+                tracing::error!("cannot list catalogs: {}", e);
+            }
+        }
+    }
 }
 
 impl CatalogProviderList for Unity {
@@ -72,21 +421,1687 @@ impl CatalogProviderList for Unity {
         self
     }
 
-    // TODO: implement this with remote catalog?
-    // function signature is very awkward.
     fn register_catalog(
         &self,
-        _name: String,
-        _catalog: Arc<dyn CatalogProvider>,
+        name: String,
+        catalog: Arc<dyn CatalogProvider>,
+    ) -> Option<Arc<dyn CatalogProvider>> {
+        self.metastore.register_catalog(name, catalog)
+    }
+
+    fn catalog_names(&self) -> Vec<String> {
+        self.metastore.catalog_names()
+    }
+
+    fn catalog(&self, name: &str) -> Option<Arc<dyn CatalogProvider>> {
+        self.metastore.catalog(name)
+    }
+}
+
+impl CatalogProviderList for Metastore {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn register_catalog(
+        &self,
+        name: String,
+        catalog: Arc<dyn CatalogProvider>,
     ) -> Option<Arc<dyn CatalogProvider>> {
-        todo!()
+        if self.options.catalog_registration == CatalogRegistration::Remote
+            && catalog.schema_names().is_empty()
+        {
+            let created = block_on(self.create_catalog(&name, None, HashMap::new()));
+            // DataFusion ignores the outcome of registering a catalog, so a
+            // failure can only be logged. The catalog is not served from
+            // memory, so that using it fails instead of losing its contents.
+            if let Err(e) = created.and_then(|r| r) {
+                tracing::error!("cannot create catalog '{}' in Unity: {}", name, e);
+            }
+            return None;
+        }
+        self.local_catalogs.insert(name, catalog)
     }
 
     fn catalog_names(&self) -> Vec<String> {
-        self.catalogs.keys().cloned().collect()
+        self.ensure_fresh();
+        let mut names = self.catalogs.names();
+        for local in self.local_catalogs.iter() {
+            if !names.contains(local.key()) {
+                names.push(local.key().clone());
+            }
+        }
+        names
     }
 
     fn catalog(&self, name: &str) -> Option<Arc<dyn CatalogProvider>> {
-        self.catalogs.get(name).cloned()
+        if let Some(catalog) = self.local_catalogs.get(name) {
+            return Some(catalog.clone());
+        }
+        self.ensure_fresh();
+        self.catalogs
+            .get_stale(name)
+            .map(|catalog| catalog as Arc<dyn CatalogProvider>)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use datafusion::{
+        arrow::{
+            array::{AsArray, Int32Array, Int64Array, StringArray},
+            datatypes::{
+                DataType as ArrowDataType, Field, Int64Type, Schema as ArrowSchema, UInt64Type,
+            },
+            record_batch::RecordBatch,
+        },
+        assert_batches_eq,
+        catalog::{schema::SchemaProvider, MemoryCatalogProvider},
+        common::{stats::Precision, ScalarValue},
+        datasource::{TableProvider, TableType},
+        error::DataFusionError,
+        parquet::arrow::ArrowWriter,
+        prelude::{SessionConfig, SessionContext},
+    };
+    use deltalake::{
+        kernel::{DataType, StructField},
+        DeltaConfigKey, DeltaOps,
+    };
+    use serde_json::{json, Value};
+    use wiremock::{
+        matchers::{body_json, body_partial_json, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::{
+        client::rest::HttpOptions,
+        test_util::*,
+        unity::options::{CatalogRegistration, ErrorPolicy, LoadingMode},
+    };
+
+    async fn malformed_server() -> MockServer {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["unity"]).await;
+        mock_schemas(&server, "unity", &["default", "broken"]).await;
+        mock_tables(
+            &server,
+            "unity",
+            "default",
+            tables_response(json!([
+                { "name": "no_location", "table_type": "MANAGED" },
+                {
+                    "name": "unreachable",
+                    "table_type": "EXTERNAL",
+                    "data_source_format": "DELTA",
+                    "storage_location": "file:///does/not/exist",
+                },
+            ])),
+        )
+        .await;
+        mock_tables(
+            &server,
+            "unity",
+            "broken",
+            ResponseTemplate::new(200).set_body_string(r#"{"tables": ["#),
+        )
+        .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_malformed_response_fails() {
+        let server = malformed_server().await;
+
+        let Err(err) = Unity::try_new(&server.uri()).await else {
+            panic!("expected malformed response to fail");
+        };
+        assert!(
+            matches!(err, UnityError::InvalidResponse { ref name, .. } if name == "unity.broken"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_response_skipped() {
+        let server = malformed_server().await;
+        let options = UnityOptions::new().with_error_policy(ErrorPolicy::SkipAndLog);
+
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let catalog = unity.catalog("unity").unwrap();
+        assert_eq!(catalog.schema_names(), vec!["default"]);
+
+        let schema = catalog.schema("default").unwrap();
+        assert!(schema.table_exist("no_location"));
+        assert!(schema.table("no_location").await.unwrap().is_none());
+        assert!(schema.table("unreachable").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unloadable_tables_are_errors() {
+        let server = malformed_server().await;
+        let client =
+            Arc::new(UnityClient::new(&server.uri(), None, HttpOptions::default()).unwrap());
+        let schema = UnitySchema::try_new(
+            client,
+            Default::default(),
+            Arc::new(DeltaTableCache::new(Duration::ZERO, HashMap::new())),
+            Weak::<Metastore>::new(),
+            "unity",
+            "default",
+        )
+        .await
+        .unwrap();
+
+        let err = scan_error(schema.table("no_location").await.unwrap()).await;
+        assert!(err.to_string().contains("unity.default.no_location"));
+        let err = scan_error(schema.table("unreachable").await.unwrap()).await;
+        assert!(err.to_string().contains("unity.default.unreachable"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lazy_loading() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["unity"]).await;
+        mock_schemas(&server, "unity", &["default"]).await;
+        mock_table(
+            &server,
+            "unity.default.events",
+            ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": "TABLE_DOES_NOT_EXIST",
+                "message": "Table not found: unity.default.events",
+            })),
+        )
+        .await;
+
+        let options = UnityOptions::new().with_loading_mode(LoadingMode::Lazy);
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        assert_eq!(requested_paths(&server).await, ["/catalogs"]);
+
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert_eq!(requested_paths(&server).await, ["/catalogs", "/schemas"]);
+
+        assert!(schema.table("events").await.unwrap().is_none());
+        assert_eq!(
+            requested_paths(&server).await,
+            ["/catalogs", "/schemas", "/tables/unity.default.events"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lazy_loading_on_current_thread_runtime() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["unity"]).await;
+        mock_schemas(&server, "unity", &["default"]).await;
+
+        // blocking would deadlock the runtime, so lookups on demand are refused
+        let lazy = UnityOptions::new().with_loading_mode(LoadingMode::Lazy);
+        let expiring = UnityOptions::new().with_table_ttl(Duration::from_secs(60));
+        for options in [lazy, expiring] {
+            let Err(err) = Unity::try_new_with_options(&server.uri(), options).await else {
+                panic!("expected a current-thread runtime to be rejected");
+            };
+            assert!(err.to_string().contains("multi-threaded"), "{}", err);
+        }
+        assert!(requested_paths(&server).await.is_empty());
+    }
+
+    /// The error reported when reading `table`, which cannot be opened.
+    async fn scan_error(table: Option<Arc<dyn TableProvider>>) -> DataFusionError {
+        let state = SessionContext::new().state();
+        let table = table.expect("tables that cannot be opened are still exposed");
+        table.scan(&state, None, &[], None).await.err().unwrap()
+    }
+
+    async fn table_server(catalogs: &[&str], tables: Value) -> MockServer {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, catalogs).await;
+        for catalog in catalogs {
+            mock_schemas(&server, catalog, &["default"]).await;
+            mock_tables(&server, catalog, "default", tables_response(tables.clone())).await;
+        }
+        server
+    }
+
+    #[tokio::test]
+    async fn test_refresh_picks_up_new_objects() {
+        let server = table_server(&["unity"], json!([])).await;
+        let unity = Unity::try_new(&server.uri()).await.unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert!(schema.table_names().is_empty());
+
+        server.reset().await;
+        let new_table = json!([{ "name": "events", "storage_location": "file:///tmp/events" }]);
+        for catalog in ["unity", "sales"] {
+            mock_schemas(&server, catalog, &["default"]).await;
+            mock_tables(
+                &server,
+                catalog,
+                "default",
+                tables_response(new_table.clone()),
+            )
+            .await;
+        }
+        mock_catalogs(&server, &["unity", "sales"]).await;
+
+        unity.refresh_schema("unity", "default").await.unwrap();
+        assert_eq!(schema.table_names(), ["events"]);
+        assert_eq!(unity.catalog_names(), ["unity"]);
+
+        unity.refresh().await.unwrap();
+        let mut catalogs = unity.catalog_names();
+        catalogs.sort();
+        assert_eq!(catalogs, ["sales", "unity"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_expired_tables_are_listed_again() {
+        let server = table_server(&["unity"], json!([])).await;
+        let options = UnityOptions::new().with_table_ttl(Duration::ZERO);
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+
+        schema.table_names();
+        schema.table_names();
+        let listings = requested_paths(&server)
+            .await
+            .into_iter()
+            .filter(|path| path == "/tables")
+            .count();
+        assert_eq!(listings, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_background_refresh() {
+        let server = table_server(&["unity"], json!([])).await;
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+
+        server.reset().await;
+        mock_catalogs(&server, &["unity", "sales"]).await;
+        mock_schemas(&server, "unity", &["default"]).await;
+        mock_schemas(&server, "sales", &[]).await;
+        mock_tables(&server, "unity", "default", tables_response(json!([]))).await;
+
+        let handle = unity.start_background_refresh(Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(unity.catalog_names().len(), 2);
+
+        drop(unity);
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queries_see_new_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1, 2]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "name": "ids", "storage_location": location }]),
+        )
+        .await;
+
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        let count = |ctx: SessionContext| async move {
+            let batches = ctx
+                .sql("SELECT count(*) FROM unity.default.ids")
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let counts = batches[0].column(0).as_primitive::<Int64Type>();
+            counts.value(0)
+        };
+
+        assert_eq!(count(ctx.clone()).await, 2);
+        append_ids(table, &[3]).await;
+        assert_eq!(count(ctx.clone()).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_parquet_and_csv_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events");
+        for (day, ids) in [("2024-01-01", vec![1, 2]), ("2024-01-02", vec![3])] {
+            let partition = events.join(format!("day={}", day));
+            std::fs::create_dir_all(&partition).unwrap();
+            let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+                "id",
+                ArrowDataType::Int64,
+                true,
+            )]));
+            let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(ids))])
+                .unwrap();
+            let file = std::fs::File::create(partition.join("part-0.parquet")).unwrap();
+            let mut writer = ArrowWriter::try_new(file, schema, None).unwrap();
+            writer.write(&batch).unwrap();
+            writer.close().unwrap();
+        }
+        std::fs::write(events.join("_SUCCESS"), "").unwrap();
+        let raw = dir.path().join("raw");
+        std::fs::create_dir_all(&raw).unwrap();
+        std::fs::write(raw.join("part-0.csv"), "id;name\n10;a\n20;b\n").unwrap();
+
+        let server = table_server(
+            &["unity"],
+            json!([
+                {
+                    "name": "events",
+                    "data_source_format": "PARQUET",
+                    "storage_location": events.to_str().unwrap(),
+                    "columns": [
+                        { "name": "id", "type_name": "LONG", "position": 0, "nullable": true },
+                        { "name": "day", "type_name": "DATE", "position": 1, "nullable": true, "partition_index": 0 },
+                    ],
+                },
+                {
+                    "name": "raw",
+                    "data_source_format": "CSV",
+                    "storage_location": raw.to_str().unwrap(),
+                    "columns": [
+                        { "name": "id", "type_name": "LONG", "position": 0, "nullable": true },
+                        { "name": "name", "type_name": "STRING", "position": 1, "nullable": true },
+                    ],
+                    "properties": { "header": "true", "delimiter": ";" },
+                },
+            ]),
+        )
+        .await;
+
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        let sum = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<Int64Type>().value(0)
+            }
+        };
+
+        assert_eq!(sum("SELECT sum(id) FROM unity.default.events").await, 6);
+        assert_eq!(
+            sum("SELECT sum(id) FROM unity.default.events WHERE day = '2024-01-01'").await,
+            3
+        );
+        assert_eq!(sum("SELECT sum(id) FROM unity.default.raw").await, 30);
+    }
+
+    #[tokio::test]
+    async fn test_views_are_planned_against_unity() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1, 2, 3]).await;
+        let server = table_server(
+            &["unity"],
+            json!([
+                { "name": "ids", "table_type": "EXTERNAL", "storage_location": location },
+                {
+                    "name": "large_ids",
+                    "table_type": "VIEW",
+                    "view_definition": "SELECT id FROM ids WHERE id > 1",
+                },
+                {
+                    "name": "id_count",
+                    "table_type": "MATERIALIZED_VIEW",
+                    "storage_location": "s3://pipelines/id_count",
+                    "view_definition": "SELECT count(*) AS n FROM ids",
+                },
+                {
+                    "name": "id_stream",
+                    "table_type": "STREAMING_TABLE",
+                    "storage_location": "s3://pipelines/id_stream",
+                },
+            ]),
+        )
+        .await;
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        let view = schema.table("large_ids").await.unwrap().unwrap();
+        assert_eq!(view.table_type(), TableType::View);
+
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity);
+        let batches = ctx
+            .sql("SELECT sum(id) FROM unity.default.large_ids")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 5);
+
+        let batches = ctx
+            .sql("SELECT n FROM unity.default.id_count")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 3);
+        let err = scan_error(schema.table("id_stream").await.unwrap()).await;
+        assert!(
+            err.to_string()
+                .ends_with("streaming table has no definition"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_columns_are_described_without_storage() {
+        let server = table_server(
+            &["unity"],
+            json!([{
+                "name": "events",
+                "storage_location": "s3://missing-bucket/events",
+                "columns": [
+                    { "name": "id", "type_name": "LONG", "position": 0, "nullable": false },
+                    { "name": "day", "type_name": "DATE", "position": 1, "nullable": true, "partition_index": 0 },
+                ],
+            }]),
+        )
+        .await;
+
+        let config = SessionConfig::new().with_information_schema(true);
+        let mut ctx = SessionContext::new_with_config(config);
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        let batches = ctx
+            .sql(
+                "SELECT column_name, data_type FROM information_schema.columns \
+                 WHERE table_catalog = 'unity' ORDER BY ordinal_position",
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+
+        let names = batches[0].column(0).as_string::<i32>();
+        let types = batches[0].column(1).as_string::<i32>();
+        assert_eq!(names.iter().collect::<Vec<_>>(), [Some("id"), Some("day")]);
+        assert_eq!(
+            types.iter().collect::<Vec<_>>(),
+            [Some("Int64"), Some("Date32")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_drift_is_detected_on_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{
+                "name": "ids",
+                "storage_location": location,
+                "columns": [{ "name": "id", "type_name": "STRING", "position": 0, "nullable": true }],
+            }]),
+        )
+        .await;
+
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        let err = ctx
+            .sql("SELECT id FROM unity.default.ids")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("column 'id' has type Utf8 in the catalog but Int64 in the Delta log"));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_format_is_reported() {
+        let server = table_server(
+            &["unity"],
+            json!([{ "name": "logs", "data_source_format": "ORC", "storage_location": "/tmp/logs" }]),
+        )
+        .await;
+        let unity = Unity::try_new(&server.uri()).await.unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+
+        let err = scan_error(schema.table("logs").await.unwrap()).await;
+        assert!(err
+            .to_string()
+            .contains("tables stored as ORC are not supported"));
+    }
+
+    /// Milliseconds since the Unix epoch, `from_now` in the future.
+    fn expiration_time(from_now: Duration) -> i64 {
+        let at = SystemTime::now() + from_now;
+        at.duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
+    }
+
+    #[tokio::test]
+    async fn test_tables_are_opened_with_vended_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(root).await, &[1]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "table_id": "7f3e", "name": "ids", "storage_location": remote_location(root) }]),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .and(body_json(
+                json!({ "table_id": "7f3e", "operation": "READ" }),
+            ))
+            .respond_with(aws_credentials_response(
+                "AKIA1",
+                expiration_time(Duration::from_secs(3600)),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_credential_vending(true);
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert!(schema.table("ids").await.unwrap().is_some());
+
+        let opened = storage_options_of(root);
+        let options = opened.last().unwrap();
+        assert_eq!(options["aws_access_key_id"], "AKIA1");
+        assert_eq!(options["aws_secret_access_key"], "secret");
+        assert_eq!(options["aws_session_token"], "session");
+    }
+
+    #[tokio::test]
+    async fn test_vended_credentials_are_refreshed_before_they_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(root).await, &[1]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "table_id": "7f3e", "name": "ids", "storage_location": remote_location(root) }]),
+        )
+        .await;
+        // within the refresh margin of five minutes
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .respond_with(aws_credentials_response(
+                "AKIA1",
+                expiration_time(Duration::from_secs(60)),
+            ))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .respond_with(aws_credentials_response(
+                "AKIA2",
+                expiration_time(Duration::from_secs(3600)),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new()
+            .with_credential_vending(true)
+            .with_table_staleness(Duration::from_secs(3600));
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        for _ in 0..3 {
+            assert!(schema.table("ids").await.unwrap().is_some());
+        }
+
+        let keys: Vec<_> = storage_options_of(root)
+            .iter()
+            .map(|options| options["aws_access_key_id"].clone())
+            .collect();
+        assert_eq!(keys.first().unwrap(), "AKIA1");
+        assert_eq!(keys.last().unwrap(), "AKIA2");
+    }
+
+    #[tokio::test]
+    async fn test_credentials_are_not_vended_for_local_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1]).await;
+        let file_url = format!("file://{}", location);
+        let server = table_server(
+            &["unity"],
+            json!([
+                { "table_id": "7f3e", "name": "ids", "storage_location": location },
+                { "table_id": "8a1b", "name": "urls", "storage_location": file_url },
+            ]),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_credential_vending(true);
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert!(schema.table("ids").await.unwrap().is_some());
+        assert!(schema.table("urls").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_insert_into_and_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(root).await, &[1, 2]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{
+                "table_id": "7f3e",
+                "name": "ids",
+                "storage_location": remote_location(root),
+                "columns": [{ "name": "id", "type_name": "LONG", "position": 0, "nullable": true }],
+            }]),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .and(body_json(
+                json!({ "table_id": "7f3e", "operation": "READ_WRITE" }),
+            ))
+            .respond_with(aws_credentials_response(
+                "AKIAWRITE",
+                expiration_time(Duration::from_secs(3600)),
+            ))
+            .expect(4)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .and(body_json(
+                json!({ "table_id": "7f3e", "operation": "READ" }),
+            ))
+            .respond_with(aws_credentials_response(
+                "AKIAREAD",
+                expiration_time(Duration::from_secs(3600)),
+            ))
+            .mount(&server)
+            .await;
+
+        // cached snapshots must not hide the commits made by inserts
+        let options = UnityOptions::new()
+            .with_credential_vending(true)
+            .with_table_staleness(Duration::from_secs(3600));
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(unity));
+        let run = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<Int64Type>().value(0)
+            }
+        };
+        let count = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+            }
+        };
+
+        assert_eq!(run("SELECT sum(id) FROM unity.default.ids").await, 3);
+        assert_eq!(
+            count("INSERT INTO unity.default.ids VALUES (3), (4)").await,
+            2
+        );
+        assert_eq!(run("SELECT sum(id) FROM unity.default.ids").await, 10);
+        assert_eq!(
+            count("INSERT OVERWRITE unity.default.ids VALUES (5)").await,
+            1
+        );
+        assert_eq!(run("SELECT sum(id) FROM unity.default.ids").await, 5);
+
+        assert_eq!(
+            count(
+                "INSERT INTO unity.default.ids SELECT id FROM unity.default.ids WHERE id % 2 = 0"
+            )
+            .await,
+            0
+        );
+        assert_eq!(run("SELECT count(*) FROM unity.default.ids").await, 1);
+        assert_eq!(
+            count(
+                "INSERT OVERWRITE unity.default.ids SELECT id FROM unity.default.ids WHERE id % 2 = 0"
+            )
+            .await,
+            0
+        );
+        assert_eq!(run("SELECT count(*) FROM unity.default.ids").await, 0);
+        assert!(storage_options_of(root)
+            .iter()
+            .any(|options| options["aws_access_key_id"] == "AKIAWRITE"));
+    }
+
+    #[tokio::test]
+    async fn test_vended_credentials_are_not_offered_to_file_tables() {
+        let server = table_server(
+            &["unity"],
+            json!([{
+                "name": "raw",
+                "data_source_format": "PARQUET",
+                "storage_location": "s3://bucket/raw",
+                "columns": [{ "name": "id", "type_name": "LONG", "position": 0, "nullable": true }],
+            }]),
+        )
+        .await;
+
+        let options = UnityOptions::new().with_credential_vending(true);
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        let err = scan_error(schema.table("raw").await.unwrap()).await;
+        assert!(
+            err.to_string()
+                .ends_with("tables stored as PARQUET cannot be read with vended credentials"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_statistics_of_scanned_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1, 2, 3]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{
+                "name": "ids",
+                "storage_location": location,
+                "columns": [{ "name": "id", "type_name": "LONG", "position": 0, "nullable": true }],
+            }]),
+        )
+        .await;
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        let table = schema.table("ids").await.unwrap().unwrap();
+        assert!(table.statistics().is_none());
+
+        ctx.sql("SELECT * FROM unity.default.ids")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let statistics = table.statistics().unwrap();
+        assert_eq!(statistics.num_rows, Precision::Exact(3));
+        assert_eq!(
+            statistics.column_statistics[0].min_value,
+            Precision::Exact(ScalarValue::Int64(Some(1)))
+        );
+        assert!(table.constraints().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_update_and_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1, 2, 3, 4]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "name": "ids", "storage_location": location }]),
+        )
+        .await;
+
+        let mut ctx =
+            SessionContext::new_with_state(crate::enable_dml(SessionContext::new().state()));
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        let count = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                assert_eq!(batches[0].schema().field(0).name(), "count");
+                batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+            }
+        };
+        let sum = || {
+            let ctx = ctx.clone();
+            async move {
+                let sql = "SELECT sum(id) FROM unity.default.ids";
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<Int64Type>().value(0)
+            }
+        };
+
+        assert_eq!(count("DELETE FROM unity.default.ids WHERE id > 3").await, 1);
+        assert_eq!(sum().await, 6);
+        assert_eq!(
+            count("UPDATE unity.default.ids SET id = id * 10 WHERE ids.id = 1").await,
+            1
+        );
+        assert_eq!(sum().await, 15);
+
+        let merged = crate::execute_merge(
+            &ctx,
+            "MERGE INTO unity.default.ids AS t \
+             USING (SELECT * FROM (VALUES (2), (5)) AS v(id)) AS s ON t.id = s.id \
+             WHEN MATCHED THEN DELETE \
+             WHEN NOT MATCHED THEN INSERT (id) VALUES (s.id)",
+        )
+        .await
+        .unwrap()
+        .collect()
+        .await
+        .unwrap();
+        let counts = merged[0]
+            .columns()
+            .iter()
+            .map(|column| column.as_primitive::<UInt64Type>().value(0))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [1, 0, 1]);
+        assert_eq!(sum().await, 18);
+
+        let err = crate::execute_merge(&ctx, "DELETE FROM unity.default.ids")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("MERGE INTO"), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_time_travel() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1, 2]).await;
+        append_ids(table, &[3]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "name": "ids", "storage_location": location }]),
+        )
+        .await;
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let config = SessionConfig::new().with_default_catalog_and_schema("unity", "default");
+        let mut ctx = SessionContext::new_with_config(config);
+        unity.register_table_functions(&ctx);
+        ctx.register_catalog_list(unity);
+        let sum = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<Int64Type>().value(0)
+            }
+        };
+
+        assert_eq!(sum("SELECT sum(id) FROM unity.default.ids").await, 6);
+        assert_eq!(
+            sum(r#"SELECT sum(id) FROM unity.default."ids@v1""#).await,
+            3
+        );
+        assert_eq!(
+            sum("SELECT sum(id) FROM unity_table_version('unity.default.ids', 1)").await,
+            3
+        );
+        assert_eq!(
+            sum("SELECT sum(id) FROM unity_table_as_of('ids', '2999-01-01T00:00:00Z')").await,
+            6
+        );
+        assert_eq!(
+            sum("SELECT count(*) FROM unity_table_version('ids', 0)").await,
+            0
+        );
+
+        let error = ctx
+            .sql(r#"INSERT INTO unity.default."ids@v1" VALUES (4)"#)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("read-only"), "{}", error);
+        assert!(ctx
+            .sql("SELECT * FROM unity_table_version('unity.default.missing', 1)")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_table_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cdf_location = dir.path().join("cdf");
+        let cdf_location = cdf_location.to_str().unwrap();
+        let table = DeltaOps::try_from_uri(cdf_location)
+            .await
+            .unwrap()
+            .create()
+            .with_columns([StructField::new("id", DataType::LONG, true)])
+            .with_configuration_property(DeltaConfigKey::EnableChangeDataFeed, Some("true"))
+            .await
+            .unwrap();
+        let table = append_ids(table, &[1, 2]).await;
+        append_ids(table, &[3]).await;
+        // a commit that deleted id 1 and recorded it in a change data file
+        let change_data = dir.path().join("cdf/_change_data");
+        std::fs::create_dir_all(&change_data).unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", ArrowDataType::Int64, true),
+            Field::new("_change_type", ArrowDataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["delete"])),
+            ],
+        )
+        .unwrap();
+        let file = std::fs::File::create(change_data.join("cdc-0.parquet")).unwrap();
+        let mut writer = ArrowWriter::try_new(file, schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        let size = std::fs::metadata(change_data.join("cdc-0.parquet"))
+            .unwrap()
+            .len();
+        let commit = [
+            json!({ "commitInfo": { "timestamp": 1700000000000i64, "operation": "DELETE" } }),
+            json!({ "cdc": {
+                "path": "_change_data/cdc-0.parquet",
+                "partitionValues": {},
+                "size": size,
+                "dataChange": false,
+            } }),
+        ];
+        let commit = commit.map(|action| action.to_string()).join("\n");
+        let log = dir.path().join("cdf/_delta_log");
+        std::fs::write(log.join("00000000000000000003.json"), commit).unwrap();
+        let plain_location = dir.path().join("plain");
+        let plain_location = plain_location.to_str().unwrap();
+        append_ids(create_delta_table(plain_location).await, &[1]).await;
+        let server = table_server(
+            &["unity"],
+            json!([
+                { "name": "cdf", "storage_location": cdf_location },
+                { "name": "plain", "storage_location": plain_location },
+                {
+                    "name": "disabled",
+                    "storage_location": cdf_location,
+                    "properties": { "delta.enableChangeDataFeed": "false" },
+                },
+            ]),
+        )
+        .await;
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let mut ctx = SessionContext::new();
+        unity.register_table_functions(&ctx);
+        ctx.register_catalog_list(unity);
+
+        let batches = ctx
+            .sql(
+                "SELECT id, _change_type, _commit_version \
+                 FROM table_changes('unity.default.cdf', 2) ORDER BY id",
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_batches_eq!(
+            [
+                "+----+--------------+-----------------+",
+                "| id | _change_type | _commit_version |",
+                "+----+--------------+-----------------+",
+                "| 1  | delete       | 3               |",
+                "| 3  | insert       | 2               |",
+                "+----+--------------+-----------------+",
+            ],
+            &batches
+        );
+        let plan = ctx
+            .sql("EXPLAIN SELECT id FROM table_changes('unity.default.cdf', 0)")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let plan = plan[0].column(1).as_string::<i32>().value(1).to_string();
+        assert!(plan.contains("UnionExec"), "{}", plan);
+        assert!(plan.contains("ParquetExec"), "{}", plan);
+        let batches = ctx
+            .sql("SELECT count(*) FROM table_changes('unity.default.cdf', 0, 1)")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 2);
+
+        for table in ["plain", "disabled"] {
+            let sql = format!("SELECT * FROM table_changes('unity.default.{}', 0)", table);
+            let error = ctx.sql(&sql).await.unwrap_err();
+            assert!(error.to_string().contains("change data feed"), "{}", error);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_describe_history_and_detail() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1, 2]).await;
+        append_ids(table, &[3]).await;
+        let server = table_server(
+            &["unity"],
+            json!([{ "name": "ids", "storage_location": location }]),
+        )
+        .await;
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let mut ctx =
+            SessionContext::new_with_state(crate::enable_dml(SessionContext::new().state()));
+        unity.register_table_functions(&ctx);
+        ctx.register_catalog_list(unity);
+        let query = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move { ctx.sql(sql).await.unwrap().collect().await.unwrap() }
+        };
+
+        query("DELETE FROM unity.default.ids WHERE id = 3").await;
+
+        let batches = query(
+            "SELECT version, operation, \"operationParameters\"['mode'] AS mode, \
+             \"operationMetrics\"['num_deleted_rows'] AS deleted \
+             FROM describe_history('unity.default.ids')",
+        )
+        .await;
+        assert_batches_eq!(
+            [
+                "+---------+--------------+---------------+---------+",
+                "| version | operation    | mode          | deleted |",
+                "+---------+--------------+---------------+---------+",
+                "| 3       | DELETE       |               | 1       |",
+                "| 2       | WRITE        | Append        |         |",
+                "| 1       | WRITE        | Append        |         |",
+                "| 0       | CREATE TABLE | ErrorIfExists |         |",
+                "+---------+--------------+---------------+---------+",
+            ],
+            &batches
+        );
+        let batches = query("SELECT version FROM describe_history('unity.default.ids', 1)").await;
+        assert_eq!(batches[0].num_rows(), 1);
+
+        let batches = query(
+            "SELECT format, name, version, \"partitionColumns\", \"numFiles\", \
+             \"minReaderVersion\", \"minWriterVersion\" \
+             FROM describe_detail('unity.default.ids')",
+        )
+        .await;
+        assert_batches_eq!(
+            [
+                "+--------+-------------------+---------+------------------+----------+------------------+------------------+",
+                "| format | name              | version | partitionColumns | numFiles | minReaderVersion | minWriterVersion |",
+                "+--------+-------------------+---------+------------------+----------+------------------+------------------+",
+                "| delta  | unity.default.ids | 3       | []               | 1        | 1                | 2                |",
+                "+--------+-------------------+---------+------------------+----------+------------------+------------------+",
+            ],
+            &batches
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partition_filters_are_exact() {
+        let dir = tempfile::tempdir().unwrap();
+        let columns = [
+            StructField::new("id", DataType::LONG, true),
+            StructField::new("year", DataType::INTEGER, true),
+        ];
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", ArrowDataType::Int64, true),
+            Field::new("year", ArrowDataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(Int32Array::from(vec![
+                    Some(2023),
+                    Some(2024),
+                    None,
+                    Some(2023),
+                ])),
+            ],
+        )
+        .unwrap();
+        let mut locations = vec![];
+        for (name, partition_columns) in [("partitioned", vec!["year"]), ("flat", vec![])] {
+            let location = dir.path().join(name);
+            let location = location.to_str().unwrap().to_string();
+            let table = DeltaOps::try_from_uri(&location)
+                .await
+                .unwrap()
+                .create()
+                .with_columns(columns.clone())
+                .with_partition_columns(partition_columns)
+                .await
+                .unwrap();
+            DeltaOps(table).write([batch.clone()]).await.unwrap();
+            locations.push(location);
+        }
+        let table = |name: &str, location: &str| {
+            json!({
+                "name": name,
+                "storage_location": location,
+                "columns": [
+                    { "name": "id", "type_name": "LONG", "position": 0, "nullable": true },
+                    { "name": "year", "type_name": "INT", "position": 1, "nullable": true, "partition_index": 0 },
+                ],
+            })
+        };
+        let server = table_server(
+            &["unity"],
+            json!([
+                table("partitioned", &locations[0]),
+                // registered as partitioned, but not partitioned in the Delta log
+                table("flat", &locations[1]),
+            ]),
+        )
+        .await;
+
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+        for name in ["partitioned", "flat"] {
+            for (filter, expected) in [
+                ("year = 2023", 5),
+                ("year IS NULL", 3),
+                ("year IN (2024, 2023)", 7),
+                ("year > 2023 OR id = 4", 6),
+            ] {
+                let sql = format!(
+                    "SELECT sum(id) FROM unity.default.{} WHERE {}",
+                    name, filter
+                );
+                let batches = ctx.sql(&sql).await.unwrap().collect().await.unwrap();
+                let sum = batches[0].column(0).as_primitive::<Int64Type>().value(0);
+                assert_eq!(sum, expected, "{}", sql);
+            }
+        }
+
+        for (name, filtered) in [("partitioned", false), ("flat", true)] {
+            let sql = format!(
+                "EXPLAIN SELECT id FROM unity.default.{} WHERE year = 2023",
+                name
+            );
+            let batches = ctx.sql(&sql).await.unwrap().collect().await.unwrap();
+            let plan = batches[0].column(1).as_string::<i32>().value(1);
+            assert_eq!(plan.contains("FilterExec"), filtered, "{}", plan);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_and_drop_schema() {
+        let server = table_server(&["unity"], json!([])).await;
+        Mock::given(method("POST"))
+            .and(path("/schemas"))
+            .and(body_json(
+                json!({ "name": "staging", "catalog_name": "unity" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schema_id": "staging", "name": "staging", "catalog_name": "unity",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mock_tables(&server, "unity", "staging", tables_response(json!([]))).await;
+        Mock::given(method("DELETE"))
+            .and(path("/schemas/unity.staging"))
+            .and(query_param("force", "true"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+        let catalog = unity.catalog("unity").unwrap();
+
+        ctx.sql("CREATE SCHEMA unity.staging").await.unwrap();
+        let mut schemas = catalog.schema_names();
+        schemas.sort();
+        assert_eq!(schemas, ["default", "staging"]);
+
+        ctx.sql("DROP SCHEMA unity.staging CASCADE").await.unwrap();
+        assert_eq!(catalog.schema_names(), ["default"]);
+        assert!(ctx.sql("DROP SCHEMA unity.staging").await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_managed_and_external_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("managed");
+        let orders = root.join("unity/default/orders");
+        let raw = dir.path().join("raw");
+        std::fs::create_dir_all(&raw).unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "id",
+            ArrowDataType::Int64,
+            true,
+        )]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![10]))])
+                .unwrap();
+        let file = std::fs::File::create(raw.join("part-0.parquet")).unwrap();
+        let mut writer = ArrowWriter::try_new(file, schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &["unity"]).await;
+        mock_schemas(&server, "unity", &["default", "staging"]).await;
+        for schema in ["default", "staging"] {
+            mock_tables(&server, "unity", schema, tables_response(json!([]))).await;
+        }
+        let id_column = json!([
+            { "name": "id", "type_name": "LONG", "type_text": "long", "position": 0, "nullable": true },
+        ]);
+        Mock::given(method("POST"))
+            .and(path("/tables"))
+            .and(body_partial_json(json!({
+                "name": "orders",
+                "catalog_name": "unity",
+                "schema_name": "default",
+                "table_type": "MANAGED",
+                "data_source_format": "DELTA",
+                "columns": id_column,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "orders", "catalog_name": "unity", "schema_name": "default",
+                "table_type": "MANAGED", "data_source_format": "DELTA",
+                "storage_location": orders.to_str().unwrap(), "columns": id_column,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/tables"))
+            .and(body_partial_json(json!({
+                "name": "raw",
+                "schema_name": "staging",
+                "table_type": "EXTERNAL",
+                "data_source_format": "PARQUET",
+                "columns": id_column,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "raw", "catalog_name": "unity", "schema_name": "staging",
+                "table_type": "EXTERNAL", "data_source_format": "PARQUET",
+                "storage_location": raw.to_str().unwrap(), "columns": id_column,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_managed_location(root.to_str().unwrap());
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        // DataFusion registers external tables in the default schema
+        let config = SessionConfig::new().with_default_catalog_and_schema("unity", "default");
+        let mut ctx = SessionContext::new_with_config(config);
+        ctx.register_catalog_list(Arc::new(unity));
+        let sum = |sql: &'static str| {
+            let ctx = ctx.clone();
+            async move {
+                let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+                batches[0].column(0).as_primitive::<Int64Type>().value(0)
+            }
+        };
+
+        ctx.sql(
+            "CREATE TABLE unity.default.orders AS SELECT * FROM (VALUES (1), (2), (3)) AS t(id)",
+        )
+        .await
+        .unwrap();
+        assert!(orders.join("_delta_log").exists());
+        assert_eq!(sum("SELECT sum(id) FROM unity.default.orders").await, 6);
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE unity.staging.raw STORED AS PARQUET LOCATION '{}/'",
+            raw.to_str().unwrap()
+        );
+        ctx.sql(&sql).await.unwrap();
+        assert_eq!(sum("SELECT sum(id) FROM unity.staging.raw").await, 10);
+        ctx.sql(&sql.replace("EXTERNAL TABLE", "EXTERNAL TABLE IF NOT EXISTS"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_managed_tables_need_a_location() {
+        let server = table_server(&["unity"], json!([])).await;
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(Unity::try_new(&server.uri()).await.unwrap()));
+
+        let err = ctx
+            .sql("CREATE TABLE unity.default.orders AS SELECT 1 AS id")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("without a managed location"));
+    }
+
+    async fn managed_table_server(location: &str) -> MockServer {
+        table_server(
+            &["unity"],
+            json!([{
+                "name": "orders",
+                "table_type": "MANAGED",
+                "data_source_format": "DELTA",
+                "storage_location": location,
+            }]),
+        )
+        .await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_managed_tables_are_written_after_registration() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("managed");
+        let server = table_server(&["unity"], json!([])).await;
+        Mock::given(method("POST"))
+            .and(path("/tables"))
+            .and(body_partial_json(json!({ "name": "rejected" })))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error_code": "INVALID_PARAMETER_VALUE",
+                "message": "managed tables are not supported",
+            })))
+            .mount(&server)
+            .await;
+        // a table already exists at the location of `taken`
+        let taken = root.join("unity/default/taken");
+        std::fs::create_dir_all(&taken).unwrap();
+        create_delta_table(taken.to_str().unwrap()).await;
+        Mock::given(method("POST"))
+            .and(path("/tables"))
+            .and(body_partial_json(json!({ "name": "taken" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "taken", "catalog_name": "unity", "schema_name": "default",
+                "table_type": "MANAGED", "data_source_format": "DELTA",
+                "storage_location": taken.to_str().unwrap(),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/tables/unity.default.taken"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_managed_location(root.to_str().unwrap());
+        let unity = Unity::try_new_with_options(&server.uri(), options)
+            .await
+            .unwrap();
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(Arc::new(unity));
+
+        for _ in 0..2 {
+            let err = ctx
+                .sql("CREATE TABLE unity.default.rejected AS SELECT 1 AS id")
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("not supported"), "{}", err);
+        }
+        assert!(!root.join("unity/default/rejected").exists());
+
+        let err = ctx
+            .sql("CREATE TABLE unity.default.taken AS SELECT 1 AS id")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unloadable_tables_can_be_dropped() {
+        let server = table_server(
+            &["unity"],
+            json!([
+                {
+                    "name": "unreachable",
+                    "table_type": "EXTERNAL",
+                    "data_source_format": "DELTA",
+                    "storage_location": "file:///does/not/exist",
+                },
+                {
+                    "name": "stale_view",
+                    "table_type": "VIEW",
+                    "view_definition": "SELECT * FROM dropped",
+                },
+            ]),
+        )
+        .await;
+        for name in ["unreachable", "stale_view"] {
+            Mock::given(method("DELETE"))
+                .and(path(format!("/tables/unity.default.{}", name)))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let unity = Arc::new(Unity::try_new(&server.uri()).await.unwrap());
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+
+        let err = ctx
+            .sql("SELECT * FROM unity.default.unreachable")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("unity.default.unreachable"),
+            "{}",
+            err
+        );
+        // DataFusion checks the table type before dropping
+        assert!(ctx
+            .sql("DROP VIEW unity.default.unreachable")
+            .await
+            .is_err());
+        assert!(ctx
+            .sql("DROP TABLE unity.default.stale_view")
+            .await
+            .is_err());
+
+        ctx.sql("DROP TABLE unity.default.unreachable")
+            .await
+            .unwrap();
+        ctx.sql("DROP VIEW unity.default.stale_view").await.unwrap();
+        ctx.sql("DROP VIEW IF EXISTS unity.default.stale_view")
+            .await
+            .unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert!(schema.table_names().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_table_purges_managed_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1, 2, 3]).await;
+        let server = managed_table_server(location).await;
+        Mock::given(method("DELETE"))
+            .and(path("/tables/unity.default.orders"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_purge_on_drop(true);
+        let unity = Arc::new(
+            Unity::try_new_with_options(&server.uri(), options)
+                .await
+                .unwrap(),
+        );
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+
+        ctx.sql("DROP TABLE unity.default.orders").await.unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert!(schema.table_names().is_empty());
+        assert!(!dir
+            .path()
+            .join("_delta_log")
+            .join("00000000000000000000.json")
+            .exists());
+
+        ctx.sql("DROP TABLE IF EXISTS unity.default.orders")
+            .await
+            .unwrap();
+        assert!(ctx.sql("DROP TABLE unity.default.orders").await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_table_dry_run_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        append_ids(create_delta_table(location).await, &[1, 2, 3]).await;
+        let server = managed_table_server(location).await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new()
+            .with_purge_on_drop(true)
+            .with_drop_dry_run(true);
+        let unity = Arc::new(
+            Unity::try_new_with_options(&server.uri(), options)
+                .await
+                .unwrap(),
+        );
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+
+        ctx.sql("DROP TABLE unity.default.orders").await.unwrap();
+        let schema = unity.catalog("unity").unwrap().schema("default").unwrap();
+        assert_eq!(schema.table_names(), ["orders"]);
+        assert!(dir
+            .path()
+            .join("_delta_log")
+            .join("00000000000000000000.json")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_endpoint() {
+        let Err(err) = Unity::try_new("not a url").await else {
+            panic!("expected invalid endpoint to fail");
+        };
+        assert!(matches!(err, UnityError::Config { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_register_catalog_does_not_panic() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &[]).await;
+        let unity = Unity::try_new(&server.uri()).await.unwrap();
+
+        let previous =
+            unity.register_catalog("local".into(), Arc::new(MemoryCatalogProvider::new()));
+        assert!(previous.is_none());
+        assert!(unity.catalog("local").is_some());
+        assert_eq!(unity.catalog_names(), ["local"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_database_creates_unity_catalog() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &[]).await;
+        mock_schemas(&server, "sales", &["default"]).await;
+        mock_tables(&server, "sales", "default", tables_response(json!([]))).await;
+        Mock::given(method("POST"))
+            .and(path("/catalogs"))
+            .and(body_json(json!({ "name": "sales" })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "id": "1", "name": "sales" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_catalog_registration(CatalogRegistration::Remote);
+        let unity = Arc::new(
+            Unity::try_new_with_options(&server.uri(), options)
+                .await
+                .unwrap(),
+        );
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+
+        ctx.sql("CREATE DATABASE sales").await.unwrap();
+        let catalog = unity.catalog("sales").unwrap();
+        assert!(catalog.as_any().is::<Catalog>());
+        assert_eq!(catalog.schema_names(), ["default"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_database_failure_is_not_hidden() {
+        let server = MockServer::start().await;
+        mock_catalogs(&server, &[]).await;
+        Mock::given(method("POST"))
+            .and(path("/catalogs"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error_code": "PERMISSION_DENIED",
+                "message": "cannot create catalogs",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UnityOptions::new().with_catalog_registration(CatalogRegistration::Remote);
+        let unity = Arc::new(
+            Unity::try_new_with_options(&server.uri(), options)
+                .await
+                .unwrap(),
+        );
+        let mut ctx = SessionContext::new();
+        ctx.register_catalog_list(unity.clone());
+
+        // DataFusion cannot report the failure, but the catalog does not exist
+        ctx.sql("CREATE DATABASE sales").await.unwrap();
+        assert!(unity.catalog("sales").is_none());
+        assert!(unity.catalog_names().is_empty());
+        let err = ctx.sql("CREATE SCHEMA sales.staging").await.unwrap_err();
+        assert!(
+            err.to_string().contains("Missing catalog 'sales'"),
+            "{}",
+            err
+        );
     }
 }
@@ -0,0 +1,273 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::client::UnityClientBuilder;
+
+/// Options controlling how a [`Unity`](super::Unity) instance talks to the
+/// server and loads metadata.
+#[derive(Debug, Clone)]
+pub struct UnityOptions {
+    /// Settings of the client, whose endpoint is given when the instance is
+    /// created.
+    pub(crate) client: UnityClientBuilder,
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) loading_mode: LoadingMode,
+    pub(crate) catalog_ttl: Option<Duration>,
+    pub(crate) schema_ttl: Option<Duration>,
+    pub(crate) table_ttl: Option<Duration>,
+    pub(crate) table_staleness: Duration,
+    pub(crate) credential_vending: bool,
+    pub(crate) managed_location: Option<String>,
+    pub(crate) purge_on_drop: bool,
+    pub(crate) drop_dry_run: bool,
+    pub(crate) catalog_registration: CatalogRegistration,
+    pub(crate) included_catalogs: Option<Vec<String>>,
+    pub(crate) excluded_catalogs: Vec<String>,
+    pub(crate) storage_options: HashMap<String, String>,
+}
+
+impl Default for UnityOptions {
+    fn default() -> Self {
+        Self {
+            client: UnityClientBuilder::new(String::new()),
+            error_policy: ErrorPolicy::default(),
+            loading_mode: LoadingMode::default(),
+            catalog_ttl: None,
+            schema_ttl: None,
+            table_ttl: None,
+            table_staleness: Duration::ZERO,
+            credential_vending: false,
+            managed_location: None,
+            purge_on_drop: false,
+            drop_dry_run: false,
+            catalog_registration: CatalogRegistration::default(),
+            included_catalogs: None,
+            excluded_catalogs: Vec::new(),
+            storage_options: HashMap::new(),
+        }
+    }
+}
+
+impl UnityOptions {
+    /// Create options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure the client that talks to Unity Catalog, e.g. its
+    /// credentials, timeouts or retry policy.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use datafusion_unity_catalog::{RetryPolicy, UnityOptions};
+    ///
+    /// let options = UnityOptions::new().with_client_builder(|client| {
+    ///     client
+    ///         .with_retry_policy(RetryPolicy::new().with_max_retries(5))
+    ///         .with_request_timeout(Some(Duration::from_secs(30)))
+    /// });
+    /// ```
+    pub fn with_client_builder(
+        mut self,
+        f: impl FnOnce(UnityClientBuilder) -> UnityClientBuilder,
+    ) -> Self {
+        self.client = f(self.client);
+        self
+    }
+
+    /// Set how failures to load individual catalogs, schemas and tables are handled.
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Set whether schemas and tables are loaded up front or on first access.
+    pub fn with_loading_mode(mut self, loading_mode: LoadingMode) -> Self {
+        self.loading_mode = loading_mode;
+        self
+    }
+
+    /// List catalogs again once the cached listing is older than `ttl`.
+    pub fn with_catalog_ttl(mut self, ttl: Duration) -> Self {
+        self.catalog_ttl = Some(ttl);
+        self
+    }
+
+    /// List the schemas of a catalog again once the cached listing is older than `ttl`.
+    pub fn with_schema_ttl(mut self, ttl: Duration) -> Self {
+        self.schema_ttl = Some(ttl);
+        self
+    }
+
+    /// Fetch table metadata again once it is older than `ttl`.
+    pub fn with_table_ttl(mut self, ttl: Duration) -> Self {
+        self.table_ttl = Some(ttl);
+        self
+    }
+
+    /// Serve opened Delta snapshots for up to `staleness` before checking the
+    /// transaction log for new commits.
+    ///
+    /// Defaults to zero, i.e. every query sees the latest committed version.
+    pub fn with_table_staleness(mut self, staleness: Duration) -> Self {
+        self.table_staleness = staleness;
+        self
+    }
+
+    /// Access table storage with short-lived credentials vended by Unity
+    /// Catalog instead of the credentials available in the environment.
+    ///
+    /// Credentials are requested per table and renewed before they expire.
+    /// Build with the `s3`, `azure` or `gcs` feature to renew them in the
+    /// middle of a scan; otherwise they are passed to `deltalake` as storage
+    /// options and the table is reopened once they are about to expire.
+    ///
+    /// Only Delta tables can be read this way. Tables stored as plain files
+    /// in object storage fail to load while credential vending is enabled.
+    /// Tables at local paths are accessed directly, without requesting
+    /// credentials.
+    pub fn with_credential_vending(mut self, enabled: bool) -> Self {
+        self.credential_vending = enabled;
+        self
+    }
+
+    /// Root location for managed tables created with `CREATE TABLE`.
+    ///
+    /// Table `catalog.schema.table` is written to `<location>/catalog/schema/table`.
+    pub fn with_managed_location(mut self, location: impl Into<String>) -> Self {
+        self.managed_location = Some(location.into());
+        self
+    }
+
+    /// Delete the data files of managed tables dropped with `DROP TABLE`.
+    ///
+    /// By default only the registration in Unity Catalog is removed and the
+    /// files are left to the server. External tables are never purged.
+    pub fn with_purge_on_drop(mut self, enabled: bool) -> Self {
+        self.purge_on_drop = enabled;
+        self
+    }
+
+    /// Only log what `DROP TABLE` would delete, without changing the catalog
+    /// or storage.
+    pub fn with_drop_dry_run(mut self, enabled: bool) -> Self {
+        self.drop_dry_run = enabled;
+        self
+    }
+
+    /// Set what happens to catalogs registered with DataFusion, e.g. by
+    /// `CREATE DATABASE`.
+    pub fn with_catalog_registration(mut self, registration: CatalogRegistration) -> Self {
+        self.catalog_registration = registration;
+        self
+    }
+
+    /// Only expose the catalogs with the given names.
+    pub fn with_included_catalogs<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.included_catalogs = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Hide the catalogs with the given names, even if they are included.
+    pub fn with_excluded_catalogs<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_catalogs = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Open table storage with the given `deltalake` storage options, e.g.
+    /// `AWS_REGION` or `AZURE_STORAGE_ACCOUNT_NAME`.
+    ///
+    /// Credentials vended by Unity Catalog take precedence over these.
+    pub fn with_storage_options<I, K, V>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.storage_options = options
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        self
+    }
+
+    /// Whether the catalog `name` is exposed.
+    pub(crate) fn includes_catalog(&self, name: &str) -> bool {
+        let included = self
+            .included_catalogs
+            .as_ref()
+            .is_none_or(|included| included.iter().any(|c| c == name));
+        included && !self.excluded_catalogs.iter().any(|c| c == name)
+    }
+}
+
+/// When schema and table metadata is fetched from the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadingMode {
+    /// Walk every catalog, schema and table while constructing [`Unity`](super::Unity).
+    #[default]
+    Eager,
+    /// Only list catalogs up front. Schemas are listed the first time a catalog
+    /// is queried and tables are fetched individually when referenced.
+    ///
+    /// Some of these lookups happen in synchronous DataFusion callbacks and
+    /// therefore require a multi-threaded Tokio runtime. The same holds for
+    /// refreshing metadata whose TTL expired. Creating a [`Unity`](super::Unity)
+    /// that needs either on a current-thread runtime fails.
+    Lazy,
+}
+
+/// What happens to catalogs registered with [`Unity`](super::Unity) through
+/// `CatalogProviderList::register_catalog`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatalogRegistration {
+    /// Serve the catalog from memory next to the Unity catalogs. It shadows a
+    /// Unity catalog with the same name.
+    #[default]
+    Local,
+    /// Create an empty catalog with the same name in Unity Catalog.
+    ///
+    /// Catalogs that already contain schemas cannot be copied to the server
+    /// and are served from memory instead. Creating the catalog requires a
+    /// multi-threaded Tokio runtime. DataFusion offers no way to report a
+    /// failure, so a catalog that cannot be created is logged and not
+    /// registered at all, and statements that use it fail.
+    Remote,
+}
+
+/// How failures to load a single catalog, schema or table are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Return the error to the caller. Tables that cannot be opened are
+    /// still listed and can be dropped, and reading them returns the error.
+    #[default]
+    Fail,
+    /// Log the error and treat the object as if it did not exist.
+    SkipAndLog,
+}
+
+impl ErrorPolicy {
+    /// Apply the policy to the result of loading `name`.
+    pub(crate) fn apply<T, E: std::fmt::Display>(
+        &self,
+        name: &str,
+        result: Result<T, E>,
+    ) -> Result<Option<T>, E> {
+        match (result, self) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(e), ErrorPolicy::SkipAndLog) => {
+                tracing::warn!("skipping '{}': {}", name, e);
+                Ok(None)
+            }
+            (Err(e), ErrorPolicy::Fail) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::future::Future;
+
+use tokio::runtime::{Builder, Handle, RuntimeFlavor};
+
+use super::error::UnityError;
+
+/// Run `future` to completion from a synchronous DataFusion callback.
+///
+/// On a multi-threaded runtime the current worker is handed over to the
+/// blocking call. A current-thread runtime cannot be blocked without also
+/// stalling the connections it drives, so this fails instead of deadlocking.
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output, UnityError> {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => Err(current_thread_runtime()),
+        Err(_) => Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map(|runtime| runtime.block_on(future))
+            .map_err(|e| UnityError::Config {
+                message: format!("cannot start runtime: {}", e),
+            }),
+    }
+}
+
+/// Fail if [`block_on`] would fail on the runtime the caller runs on.
+pub(crate) fn ensure_blocking_allowed() -> Result<(), UnityError> {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+            Err(current_thread_runtime())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn current_thread_runtime() -> UnityError {
+    UnityError::Config {
+        message: "loading metadata on demand requires a multi-threaded Tokio runtime".to_string(),
+    }
+}
@@ -1,48 +1,382 @@
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{
+    any::Any,
+    sync::{Arc, Weak},
+};
 
 use async_trait::async_trait;
 use datafusion::{
-    catalog::schema::SchemaProvider, datasource::TableProvider, error::DataFusionError,
+    catalog::{schema::SchemaProvider, CatalogProviderList},
+    common::{ResolvedTableReference, TableReference},
+    datasource::TableProvider,
+    error::DataFusionError,
 };
 
-use super::{error::UnityError, table::delta::UnityDeltaTable};
-use crate::client::{Table, UnityClient};
+use super::{
+    cache::MetadataCache,
+    error::UnityError,
+    options::{ErrorPolicy, LoadingMode, UnityOptions},
+    runtime::block_on,
+    table::{
+        cache::DeltaTableCache,
+        create::{prepare_table, write_managed_table, PreparedTable},
+        credentials::{self, VendedCredentials},
+        delta::UnityDeltaTable,
+        listing::listing_table,
+        unavailable::UnavailableTable,
+        version::TableVersion,
+        view::view_table,
+    },
+};
+use crate::client::{
+    error::ClientError,
+    rest::types::{DataSourceFormat, TableOperation, TableType},
+    Table, UnityClient,
+};
 
 pub struct UnitySchema {
     catalog_name: String,
     name: String,
     client: Arc<UnityClient>,
-    tables: HashMap<String, Table>,
+    options: Arc<UnityOptions>,
+    delta_tables: Arc<DeltaTableCache>,
+    catalog_list: Weak<dyn CatalogProviderList>,
+    tables: MetadataCache<Table>,
 }
 
 impl UnitySchema {
     pub async fn try_new(
         client: Arc<UnityClient>,
+        options: Arc<UnityOptions>,
+        delta_tables: Arc<DeltaTableCache>,
+        catalog_list: Weak<dyn CatalogProviderList>,
         catalog_name: &str,
         schema_name: &str,
     ) -> Result<UnitySchema, UnityError> {
-        let mut schema = Self {
+        let schema = Self {
             catalog_name: catalog_name.to_owned(),
             name: schema_name.to_owned(),
             client: client.clone(),
-            tables: HashMap::new(),
+            tables: MetadataCache::new(options.table_ttl),
+            options,
+            delta_tables,
+            catalog_list,
         };
-        schema.fetch().await?;
+        if schema.options.loading_mode == LoadingMode::Eager {
+            schema.fetch().await?;
+        }
         Ok(schema)
     }
 
-    async fn fetch(&mut self) -> Result<(), UnityError> {
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", self.catalog_name, self.name)
+    }
+
+    async fn fetch(&self) -> Result<(), UnityError> {
         let tables = self
             .client
             .list_tables(&self.catalog_name, &self.name)
             .await?;
 
-        for table in tables {
-            self.tables.insert(table.name().to_owned(), table);
-        }
+        self.tables.replace(
+            tables
+                .into_iter()
+                .map(|table| (table.name().to_owned(), table)),
+        );
 
         Ok(())
     }
+
+    /// Reload the tables of this schema if they have been listed before,
+    /// otherwise forget the tables that were fetched individually.
+    pub async fn refresh(&self) -> Result<(), UnityError> {
+        if self.tables.is_listed() {
+            self.fetch().await
+        } else {
+            self.tables.clear();
+            Ok(())
+        }
+    }
+
+    fn ensure_fresh(&self) {
+        if !self.tables.is_fresh() {
+            if let Err(e) = block_on(self.fetch()).and_then(|r| r) {
+                tracing::error!("cannot list tables of schema '{}': {}", self.full_name(), e);
+            }
+        }
+    }
+
+    /// Find the metadata of table `name`, fetching it from the server if the
+    /// schema has not been listed yet or the cached metadata has expired.
+    async fn lookup(&self, name: &str) -> Result<Option<Table>, UnityError> {
+        if let Some(table) = self.tables.get(name) {
+            return Ok(Some(table));
+        }
+
+        if self.tables.is_listed() {
+            if !self.tables.is_fresh() {
+                self.fetch().await?;
+            }
+            return Ok(self.tables.get_stale(name));
+        }
+
+        match self
+            .client
+            .get_table(&self.catalog_name, &self.name, name)
+            .await
+        {
+            Ok(table) => {
+                self.tables.insert(name.to_owned(), table.clone());
+                Ok(Some(table))
+            }
+            Err(ClientError::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn load_table(&self, table: &Table) -> Result<Arc<dyn TableProvider>, UnityError> {
+        // materialized views and streaming tables are maintained by
+        // pipelines outside of Unity Catalog, so only their definition can
+        // be planned here
+        let materialized = match table.table_type() {
+            Some(TableType::MaterializedView) => Some("materialized view"),
+            Some(TableType::StreamingTable) => Some("streaming table"),
+            _ => None,
+        };
+        if let (Some(kind), None) = (materialized, table.view_definition()) {
+            return Err(UnityError::Unsupported {
+                name: table.full_name(),
+                message: format!("{} has no definition", kind),
+            });
+        }
+        if table.table_type() == Some(TableType::View) || materialized.is_some() {
+            let Some(catalog_list) = self.catalog_list.upgrade() else {
+                return Err(UnityError::Config {
+                    message: "the Unity instance owning this schema has been dropped".to_string(),
+                });
+            };
+            return Ok(Arc::new(view_table(table, catalog_list).await?));
+        }
+
+        let Some(location) = table.storage_location() else {
+            return Err(UnityError::InvalidResponse {
+                name: table.full_name(),
+                message: "table has no storage location".to_string(),
+            });
+        };
+
+        match table.data_source_format() {
+            None | Some(DataSourceFormat::Delta) => {}
+            Some(format) => {
+                // DataFusion shares one object store per bucket between all
+                // file tables, so credentials vended for a single table
+                // cannot be applied to it
+                if self.vends_credentials(table) {
+                    return Err(UnityError::Unsupported {
+                        name: table.full_name(),
+                        message: format!(
+                            "tables stored as {} cannot be read with vended credentials",
+                            format!("{:?}", format).to_uppercase()
+                        ),
+                    });
+                }
+                return Ok(Arc::new(listing_table(table, format, location)?));
+            }
+        }
+
+        let provider = UnityDeltaTable::try_new(
+            table,
+            location,
+            self.delta_tables.clone(),
+            self.read_credentials(table),
+        )
+        .await?;
+        Ok(Arc::new(provider))
+    }
+
+    /// Load Delta table `name`, as of `version` if one is given.
+    pub(crate) async fn delta_table(
+        &self,
+        name: &str,
+        version: Option<TableVersion>,
+    ) -> Result<Option<(Table, UnityDeltaTable)>, UnityError> {
+        let Some(table) = self.lookup(name).await? else {
+            return Ok(None);
+        };
+        let location = match (table.data_source_format(), table.storage_location()) {
+            (None | Some(DataSourceFormat::Delta), Some(location)) => location,
+            _ => {
+                return Err(UnityError::Unsupported {
+                    name: table.full_name(),
+                    message: "table is not a Delta table".to_string(),
+                })
+            }
+        };
+
+        let tables = self.delta_tables.clone();
+        let credentials = self.read_credentials(&table);
+        let provider = match version {
+            None => UnityDeltaTable::try_new(&table, location, tables, credentials).await?,
+            Some(version) => {
+                UnityDeltaTable::try_new_at(&table, location, tables, credentials, version).await?
+            }
+        };
+        Ok(Some((table, provider)))
+    }
+
+    /// Whether storage of `table` is accessed with vended credentials.
+    fn vends_credentials(&self, table: &Table) -> bool {
+        self.options.credential_vending
+            && table.storage_location().is_some_and(credentials::is_remote)
+    }
+
+    fn read_credentials(&self, table: &Table) -> Option<Arc<VendedCredentials>> {
+        self.vends_credentials(table).then(|| {
+            Arc::new(VendedCredentials::new(
+                self.client.clone(),
+                table.clone(),
+                TableOperation::Read,
+            ))
+        })
+    }
+
+    /// Register `provider` as table `name` in Unity Catalog.
+    ///
+    /// The rows of a managed table are written once the server has accepted
+    /// it. If writing fails, the registration is removed again.
+    async fn create_table(
+        &self,
+        name: &str,
+        provider: Arc<dyn TableProvider>,
+    ) -> Result<Table, UnityError> {
+        let PreparedTable { request, data } = prepare_table(
+            &self.catalog_name,
+            &self.name,
+            name,
+            provider,
+            self.options.managed_location.as_deref(),
+        )?;
+        let table = self.client.create_table(&request).await?;
+        let Some(data) = data else {
+            return Ok(table);
+        };
+
+        let location = table
+            .storage_location()
+            .unwrap_or(&request.storage_location);
+        let written = write_managed_table(location, data, &self.options.storage_options).await;
+        if let Err(e) = written {
+            let removed = self
+                .client
+                .delete_table(&self.catalog_name, &self.name, name)
+                .await;
+            if let Err(e) = removed {
+                tracing::error!(
+                    "cannot remove table '{}' after failing to write it: {}",
+                    table.full_name(),
+                    e
+                );
+            }
+            return Err(UnityError::delta(table.full_name(), e));
+        }
+        Ok(table)
+    }
+
+    /// The schema and table named by `name` if it is qualified, as
+    /// DataFusion passes the names of external tables unresolved.
+    fn qualified(&self, name: &str) -> Option<ResolvedTableReference> {
+        let reference = TableReference::parse_str(name);
+        (!matches!(reference, TableReference::Bare { .. }))
+            .then(|| reference.resolve(&self.catalog_name, &self.name))
+    }
+
+    /// The schema that contains table `reference`.
+    fn schema_of(
+        &self,
+        reference: &ResolvedTableReference,
+    ) -> Result<Arc<dyn SchemaProvider>, DataFusionError> {
+        self.catalog_list
+            .upgrade()
+            .and_then(|catalogs| catalogs.catalog(&reference.catalog))
+            .and_then(|catalog| catalog.schema(&reference.schema))
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "schema '{}.{}' not found",
+                    reference.catalog, reference.schema
+                ))
+            })
+    }
+
+    /// Delete table `name` from Unity Catalog, and its files if it is a
+    /// managed table and purging is enabled.
+    ///
+    /// Only the metadata of the table is needed, so tables whose storage is
+    /// unreachable or whose view definition no longer plans can be dropped
+    /// too. The table is not opened, and the returned provider only
+    /// describes it.
+    async fn drop_table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>, UnityError> {
+        let Some(table) = self.lookup(name).await? else {
+            return Ok(None);
+        };
+        let provider: Arc<dyn TableProvider> = Arc::new(UnavailableTable::dropped(&table));
+        let purge_location = table
+            .storage_location()
+            .filter(|_| self.options.purge_on_drop)
+            .filter(|_| table.table_type() == Some(TableType::Managed));
+
+        if self.options.drop_dry_run {
+            match purge_location {
+                Some(location) => tracing::info!(
+                    "dry run: would drop table '{}' and delete its files at '{}'",
+                    table.full_name(),
+                    location
+                ),
+                None => tracing::info!("dry run: would drop table '{}'", table.full_name()),
+            }
+            return Ok(Some(provider));
+        }
+
+        // credentials cannot be vended once the table is gone
+        let credentials = match purge_location {
+            Some(_) if self.vends_credentials(&table) => {
+                let credentials = Arc::new(VendedCredentials::new(
+                    self.client.clone(),
+                    table.clone(),
+                    TableOperation::ReadWrite,
+                ));
+                credentials.get().await?;
+                Some(credentials)
+            }
+            _ => None,
+        };
+
+        let deleted = match self
+            .client
+            .delete_table(&self.catalog_name, &self.name, name)
+            .await
+        {
+            Ok(()) => true,
+            Err(ClientError::NotFound { .. }) => false,
+            Err(e) => return Err(e.into()),
+        };
+        self.tables.remove(name);
+        if !deleted {
+            return Ok(None);
+        }
+
+        if let Some(location) = purge_location {
+            let deleted = self
+                .delta_tables
+                .purge(&table.full_name(), location, credentials)
+                .await?;
+            tracing::debug!(
+                "deleted {} files of table '{}' at '{}'",
+                deleted,
+                table.full_name(),
+                location
+            );
+        }
+        Ok(Some(provider))
+    }
 }
 
 #[async_trait]
@@ -57,41 +391,88 @@ impl SchemaProvider for UnitySchema {
     }
 
     fn table_names(&self) -> Vec<String> {
-        self.tables.keys().cloned().collect()
+        self.ensure_fresh();
+        self.tables.names()
     }
 
+    /// Return table `name`, or a historical snapshot of it if the name ends in
+    /// `@v<version>` or `@<yyyyMMddHHmmssSSS>`, e.g. `"sales@v42"`.
     async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
-        if name == "marksheet_uniform" {
-            return Ok(None);
+        if let Some((name, version)) = TableVersion::split(name) {
+            let table = self.delta_table(name, Some(version)).await?;
+            return Ok(table.map(|(_, provider)| Arc::new(provider) as _));
         }
 
-        if self.tables.contains_key(name) {
-            let table = self.tables.get(name).unwrap();
-            let table = Arc::new(UnityDeltaTable::new(&table.storage_location()).await);
-            Ok(Some(table))
-        } else {
-            Ok(None)
-        }
+        let full_name = format!("{}.{}", self.full_name(), name);
+        let table = self.lookup(name).await;
+        let Some(table) = self
+            .options
+            .error_policy
+            .apply(&full_name, table)?
+            .flatten()
+        else {
+            return Ok(None);
+        };
+
+        // a table that cannot be opened is still exposed, so that it can be
+        // dropped, and reading it reports why it cannot be opened
+        let provider = match self.load_table(&table).await {
+            Ok(provider) => provider,
+            Err(e) => match self.options.error_policy {
+                ErrorPolicy::Fail => Arc::new(UnavailableTable::new(&table, e)),
+                ErrorPolicy::SkipAndLog => {
+                    tracing::warn!("skipping '{}': {}", table.full_name(), e);
+                    return Ok(None);
+                }
+            },
+        };
+        Ok(Some(provider))
     }
 
-    // TODO: overwrite default implementation
-    // fn register_table(
-    //     &self,
-    //     name: String,
-    //     table: Arc<dyn TableProvider>,
-    // ) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
-    //     todo!()
-    // }
+    /// Create table `name` in Unity Catalog.
+    ///
+    /// DataFusion plans `CREATE EXTERNAL TABLE` without resolving the table
+    /// name, and registers the table under its name as written in the
+    /// default schema of the session. A qualified name such as
+    /// `unity.sales.raw` is therefore resolved here, which only works if the
+    /// default schema of the session is a Unity schema. Table names that
+    /// contain a dot have to be quoted.
+    fn register_table(
+        &self,
+        name: String,
+        table: Arc<dyn TableProvider>,
+    ) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
+        if let Some(reference) = self.qualified(&name) {
+            let schema = self.schema_of(&reference)?;
+            return schema.register_table(reference.table.to_string(), table);
+        }
+        let table = block_on(self.create_table(&name, table)).and_then(|r| r)?;
+        self.tables.insert(name, table);
+        Ok(None)
+    }
 
-    // TODO: overwrite default implementation
-    // fn deregister_table(
-    //     &self,
-    //     name: &str,
-    // ) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
-    //     todo!()
-    // }
+    fn deregister_table(
+        &self,
+        name: &str,
+    ) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
+        Ok(block_on(self.drop_table(name)).and_then(|r| r)?)
+    }
 
     fn table_exist(&self, name: &str) -> bool {
-        self.tables.contains_key(name)
+        if let Some(reference) = self.qualified(name) {
+            return self
+                .schema_of(&reference)
+                .is_ok_and(|schema| schema.table_exist(&reference.table));
+        }
+        if self.tables.get(name).is_some() {
+            return true;
+        }
+        if self.tables.is_fresh() {
+            return false;
+        }
+
+        block_on(self.lookup(name))
+            .and_then(|r| r)
+            .is_ok_and(|table| table.is_some())
     }
 }
@@ -0,0 +1,402 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use dashmap::DashMap;
+use deltalake::{DeltaResult, DeltaTable, DeltaTableBuilder};
+use futures::{StreamExt, TryStreamExt};
+use url::Url;
+
+use super::{
+    credentials::{self, VendedCredentials},
+    pushdown,
+    version::TableVersion,
+};
+use crate::unity::error::UnityError;
+
+/// The number of historical snapshots kept by a [`DeltaTableCache`].
+const MAX_CACHED_VERSIONS: usize = 16;
+
+/// Delta tables opened by previous queries, keyed by storage location.
+///
+/// A cached snapshot is served as-is until it is older than the staleness
+/// bound. After that the log is checked for new commits, which are applied
+/// incrementally instead of replaying the whole transaction log.
+///
+/// Historical snapshots never change, so they are kept by location and
+/// version until their credentials expire. Only the most recently used
+/// [`MAX_CACHED_VERSIONS`] of them are kept, since every snapshot holds the
+/// file list of its version in memory.
+///
+/// Tables are opened with the default storage options, which vended
+/// credentials take precedence over.
+pub(crate) struct DeltaTableCache {
+    tables: DashMap<String, CachedTable>,
+    /// Historical snapshots, whose `checked_at` is when they were last used.
+    versions: DashMap<(String, i64), CachedTable>,
+    max_versions: usize,
+    /// Partition columns that file pruning cannot evaluate exactly, by
+    /// location, with the version they were determined for.
+    inexact_partitions: DashMap<String, (i64, Arc<HashSet<String>>)>,
+    max_staleness: Duration,
+    storage_options: HashMap<String, String>,
+}
+
+struct CachedTable {
+    table: DeltaTable,
+    checked_at: Instant,
+    /// Expiry of the credentials baked into the storage options of `table`.
+    credentials_expire_at: Option<SystemTime>,
+}
+
+impl DeltaTableCache {
+    pub fn new(max_staleness: Duration, storage_options: HashMap<String, String>) -> Self {
+        Self {
+            tables: DashMap::new(),
+            versions: DashMap::new(),
+            max_versions: MAX_CACHED_VERSIONS,
+            inexact_partitions: DashMap::new(),
+            max_staleness,
+            storage_options,
+        }
+    }
+
+    /// Return the latest snapshot of the Delta table `name` stored at `location`.
+    ///
+    /// If `credentials` are given, storage is accessed with credentials vended
+    /// by Unity Catalog instead of the ambient ones.
+    pub async fn open(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<DeltaTable, UnityError> {
+        let cached = self
+            .tables
+            .get(location)
+            .filter(|cached| {
+                !cached
+                    .credentials_expire_at
+                    .is_some_and(credentials::is_expiring)
+            })
+            .map(|cached| {
+                (
+                    cached.table.clone(),
+                    cached.checked_at,
+                    cached.credentials_expire_at,
+                )
+            });
+
+        let (table, credentials_expire_at) = match cached {
+            Some((table, checked_at, _)) if checked_at.elapsed() < self.max_staleness => {
+                return Ok(table);
+            }
+            Some((mut table, _, credentials_expire_at)) => {
+                let latest = table
+                    .get_latest_version()
+                    .await
+                    .map_err(|e| UnityError::delta(name, e))?;
+                if latest > table.version() {
+                    tracing::debug!(
+                        "updating '{}' from version {} to {}",
+                        name,
+                        table.version(),
+                        latest
+                    );
+                    table
+                        .update_incremental(None)
+                        .await
+                        .map_err(|e| UnityError::delta(name, e))?;
+                }
+                (table, credentials_expire_at)
+            }
+            None => self.load(name, location, credentials).await?,
+        };
+
+        let cached = CachedTable {
+            table: table.clone(),
+            checked_at: Instant::now(),
+            credentials_expire_at,
+        };
+        self.tables.insert(location.to_string(), cached);
+
+        Ok(table)
+    }
+
+    /// Return the snapshot of the Delta table `name` stored at `location` at
+    /// `version`.
+    pub async fn open_version(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+        version: TableVersion,
+    ) -> Result<DeltaTable, UnityError> {
+        if let TableVersion::Version(version) = version {
+            let cached = self
+                .versions
+                .get_mut(&(location.to_string(), version))
+                .filter(|cached| {
+                    !cached
+                        .credentials_expire_at
+                        .is_some_and(credentials::is_expiring)
+                })
+                .map(|mut cached| {
+                    cached.checked_at = Instant::now();
+                    cached.table.clone()
+                });
+            if let Some(table) = cached {
+                return Ok(table);
+            }
+        }
+
+        let (builder, credentials_expire_at) = self.builder(name, location, credentials).await?;
+        let builder = match version {
+            TableVersion::Version(version) => builder.with_version(version),
+            TableVersion::Timestamp(timestamp) => builder.with_timestamp(timestamp),
+        };
+        let table = builder
+            .load()
+            .await
+            .map_err(|e| UnityError::delta(name, e))?;
+
+        let cached = CachedTable {
+            table: table.clone(),
+            checked_at: Instant::now(),
+            credentials_expire_at,
+        };
+        let key = (location.to_string(), table.version());
+        if !self.versions.contains_key(&key) && self.versions.len() >= self.max_versions {
+            let least_recently_used = self
+                .versions
+                .iter()
+                .min_by_key(|cached| cached.checked_at)
+                .map(|cached| cached.key().clone());
+            if let Some(key) = least_recently_used {
+                self.versions.remove(&key);
+            }
+        }
+        self.versions.insert(key, cached);
+
+        Ok(table)
+    }
+
+    /// Return the snapshot of the table stored at `location` that a previous
+    /// query opened, without touching storage.
+    ///
+    /// `version` selects a historical snapshot instead of the latest one.
+    pub fn cached(&self, location: &str, version: Option<i64>) -> Option<DeltaTable> {
+        match version {
+            None => self.tables.get(location).map(|cached| cached.table.clone()),
+            Some(version) => self
+                .versions
+                .get(&(location.to_string(), version))
+                .map(|cached| cached.table.clone()),
+        }
+    }
+
+    /// The partition columns of `table`, stored at `location`, that file
+    /// pruning cannot evaluate exactly.
+    ///
+    /// Determining them reads the statistics of every file, so they are
+    /// computed once per version.
+    pub fn inexact_partition_columns(
+        &self,
+        location: &str,
+        table: &DeltaTable,
+    ) -> DeltaResult<Arc<HashSet<String>>> {
+        let cached = self
+            .inexact_partitions
+            .get(location)
+            .filter(|cached| cached.0 == table.version())
+            .map(|cached| cached.1.clone());
+        if let Some(columns) = cached {
+            return Ok(columns);
+        }
+        let columns = Arc::new(pushdown::inexact_partition_columns(table)?);
+        self.inexact_partitions
+            .insert(location.to_string(), (table.version(), columns.clone()));
+        Ok(columns)
+    }
+
+    /// Load the latest snapshot of the table stored at `location` for a write,
+    /// bypassing the cache so that `credentials` are used for the commit.
+    pub async fn open_for_write(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<DeltaTable, UnityError> {
+        let (table, _) = self.load(name, location, credentials).await?;
+        Ok(table)
+    }
+
+    /// Forget the snapshot of the table stored at `location`, so that the
+    /// next [`open`](Self::open) loads it again.
+    pub fn invalidate(&self, location: &str) {
+        self.tables.remove(location);
+    }
+
+    /// Forget the snapshot of the table stored at `location` and delete all
+    /// files below it, returning the number of deleted files.
+    pub async fn purge(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<usize, UnityError> {
+        self.invalidate(location);
+        self.versions.retain(|(cached, _), _| cached != location);
+        self.inexact_partitions.remove(location);
+
+        let (builder, _) = self.builder(name, location, credentials).await?;
+        let store = builder
+            .build_storage()
+            .map_err(|e| UnityError::delta(name, e))?
+            .object_store();
+        let files = store.list(None).map_ok(|meta| meta.location).boxed();
+        store
+            .delete_stream(files)
+            .try_fold(0, |deleted, _| async move { Ok(deleted + 1) })
+            .await
+            .map_err(|e| UnityError::delta(name, e.into()))
+    }
+
+    /// Load the latest snapshot of a Delta table, returning it together with the
+    /// expiry of any static credentials it was opened with.
+    ///
+    /// Vended credentials are preferred through an object store that refreshes
+    /// them on demand. If no such store is available for the storage system, the
+    /// current credentials are passed as storage options instead and the table
+    /// has to be opened again before they expire.
+    async fn load(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<(DeltaTable, Option<SystemTime>), UnityError> {
+        let (builder, expires_at) = self.builder(name, location, credentials).await?;
+        let table = builder
+            .load()
+            .await
+            .map_err(|e| UnityError::delta(name, e))?;
+        Ok((table, expires_at))
+    }
+
+    /// Configure access to the table at `location`, see [`load`](Self::load).
+    async fn builder(
+        &self,
+        name: &str,
+        location: &str,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<(DeltaTableBuilder, Option<SystemTime>), UnityError> {
+        let mut builder = DeltaTableBuilder::from_uri(location)
+            .with_storage_options(self.storage_options.clone());
+        let mut expires_at = None;
+        if let Some(credentials) = credentials {
+            let store = match Url::parse(location) {
+                Ok(url) => credentials::object_store(name, &url, &credentials)?.map(|s| (s, url)),
+                Err(_) => None,
+            };
+            builder = match store {
+                Some((store, url)) => builder.with_storage_backend(store, url),
+                None => {
+                    let vended = credentials.get().await?;
+                    expires_at = credentials::expires_at(&vended);
+                    let mut options = self.storage_options.clone();
+                    options.extend(credentials::storage_options(&vended));
+                    builder.with_storage_options(options)
+                }
+            };
+        }
+        Ok((builder, expires_at))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{append_ids, create_delta_table};
+
+    #[tokio::test]
+    async fn test_open_applies_new_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = create_delta_table(location).await;
+        let cache = DeltaTableCache::new(Duration::ZERO, HashMap::new());
+
+        assert_eq!(cache.open("t", location, None).await.unwrap().version(), 0);
+        append_ids(table, &[1, 2, 3]).await;
+        assert_eq!(cache.open("t", location, None).await.unwrap().version(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_serves_snapshot_within_staleness_bound() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = create_delta_table(location).await;
+        let cache = DeltaTableCache::new(Duration::from_secs(3600), HashMap::new());
+
+        assert_eq!(cache.open("t", location, None).await.unwrap().version(), 0);
+        append_ids(table, &[1, 2, 3]).await;
+        assert_eq!(cache.open("t", location, None).await.unwrap().version(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_open_version_loads_historical_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1, 2, 3]).await;
+        append_ids(table, &[4]).await;
+        let cache = DeltaTableCache::new(Duration::ZERO, HashMap::new());
+
+        let open = |version| cache.open_version("t", location, None, version);
+        assert_eq!(open(TableVersion::Version(1)).await.unwrap().version(), 1);
+        assert_eq!(
+            open(TableVersion::Timestamp(chrono::Utc::now()))
+                .await
+                .unwrap()
+                .version(),
+            2
+        );
+        assert!(open(TableVersion::Version(7)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inexact_partition_columns_are_computed_once_per_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1]).await;
+        let cache = DeltaTableCache::new(Duration::ZERO, HashMap::new());
+
+        let first = cache.inexact_partition_columns(location, &table).unwrap();
+        let again = cache.inexact_partition_columns(location, &table).unwrap();
+        assert!(first.is_empty());
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let table = append_ids(table, &[2]).await;
+        let updated = cache.inexact_partition_columns(location, &table).unwrap();
+        assert!(!Arc::ptr_eq(&first, &updated));
+    }
+
+    #[tokio::test]
+    async fn test_open_version_keeps_recently_used_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().to_str().unwrap();
+        let table = append_ids(create_delta_table(location).await, &[1]).await;
+        append_ids(table, &[2]).await;
+        let mut cache = DeltaTableCache::new(Duration::ZERO, HashMap::new());
+        cache.max_versions = 2;
+
+        let open =
+            |version| cache.open_version("t", location, None, TableVersion::Version(version));
+        for version in [0, 1, 0, 2] {
+            open(version).await.unwrap();
+        }
+        let mut cached: Vec<_> = cache.versions.iter().map(|cached| cached.key().1).collect();
+        cached.sort();
+        assert_eq!(cached, [0, 2]);
+    }
+}
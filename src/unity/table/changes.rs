@@ -0,0 +1,334 @@
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::TimeZone;
+use datafusion::{
+    arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    common::{ScalarValue, Statistics},
+    datasource::{
+        file_format::{parquet::ParquetFormat, FileFormat},
+        listing::PartitionedFile,
+        physical_plan::FileScanConfig,
+        TableProvider, TableType,
+    },
+    error::DataFusionError,
+    execution::context::SessionState,
+    logical_expr::Expr,
+    physical_plan::{empty::EmptyExec, union::UnionExec, ExecutionPlan},
+};
+use deltalake::{
+    kernel::Action,
+    logstore::{get_actions, LogStoreRef},
+    storage::object_store::{path::Path, ObjectMeta},
+    DeltaTable, DeltaTableError,
+};
+
+use super::delta::register_store;
+use crate::unity::error::UnityError;
+
+const ENABLE_CHANGE_DATA_FEED: &str = "delta.enableChangeDataFeed";
+const CHANGE_TYPE: &str = "_change_type";
+const COMMIT_VERSION: &str = "_commit_version";
+const COMMIT_TIMESTAMP: &str = "_commit_timestamp";
+
+/// The change data feed of a Delta table, read by `table_changes`.
+///
+/// Every row carries the columns of the table followed by `_change_type`,
+/// `_commit_version` and `_commit_timestamp`. The files to read are listed
+/// when the provider is created, so later commits are not picked up.
+///
+/// Commits that wrote change data files are read from those files, which
+/// record the change type of every row. The data files added by other
+/// commits are read as inserts.
+pub(crate) struct TableChanges {
+    schema: SchemaRef,
+    log_store: LogStoreRef,
+    /// Change data files, whose rows carry `_change_type`.
+    changes: FileScanConfig,
+    /// Data files added by commits without change data files.
+    inserts: FileScanConfig,
+}
+
+/// A file of the change data feed, with the commit that wrote it.
+struct ChangeFile {
+    version: i64,
+    timestamp: i64,
+    path: String,
+    size: i64,
+    partition_values: HashMap<String, Option<String>>,
+}
+
+impl TableChanges {
+    /// Read the changes committed to `table` from `start` up to and
+    /// including `end`, or the latest version.
+    ///
+    /// The change data feed has to be enabled through the table properties
+    /// registered in Unity Catalog, or in the Delta log if Unity Catalog does
+    /// not record the property.
+    pub async fn try_new(
+        name: &str,
+        table: DeltaTable,
+        properties: &HashMap<String, String>,
+        start: i64,
+        end: Option<i64>,
+    ) -> Result<Self, UnityError> {
+        let delta = |e| UnityError::delta(name, e);
+        let metadata = table.metadata().map_err(delta)?;
+        let enabled = match properties.get(ENABLE_CHANGE_DATA_FEED) {
+            Some(enabled) => enabled.eq_ignore_ascii_case("true"),
+            None => metadata
+                .configuration
+                .get(ENABLE_CHANGE_DATA_FEED)
+                .and_then(|enabled| enabled.as_deref())
+                .is_some_and(|enabled| enabled.eq_ignore_ascii_case("true")),
+        };
+        if !enabled {
+            return Err(UnityError::Unsupported {
+                name: name.to_string(),
+                message: format!(
+                    "change data feed is not enabled, set table property '{}' to true",
+                    ENABLE_CHANGE_DATA_FEED
+                ),
+            });
+        }
+
+        let log_store = table.log_store();
+        let end = match end {
+            Some(end) => end,
+            None => log_store.get_latest_version(start).await.map_err(delta)?,
+        };
+        if end < start {
+            return Err(delta(DeltaTableError::ChangeDataInvalidVersionRange {
+                start,
+                end,
+            }));
+        }
+        let (changes, inserts) = list_files(&log_store, start, end).await.map_err(delta)?;
+
+        let table_schema = TableProvider::schema(&table);
+        let partition_columns = &metadata.partition_columns;
+        let (partitions, data): (Vec<_>, Vec<_>) = table_schema
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .partition(|field| partition_columns.contains(field.name()));
+        let change_type = Field::new(CHANGE_TYPE, DataType::Utf8, true);
+        let commit = [
+            Field::new(COMMIT_VERSION, DataType::Int64, true),
+            Field::new(
+                COMMIT_TIMESTAMP,
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+        ];
+
+        // Both scans return the data columns, the change type, the commit and
+        // the table partitions, in that order. The change type is stored in
+        // change data files, and is a constant partition value for inserts.
+        let changes_schema = Arc::new(Schema::new(
+            data.iter()
+                .cloned()
+                .chain([change_type.clone()])
+                .collect::<Vec<_>>(),
+        ));
+        let changes_partitions = commit.iter().chain(&partitions).cloned().collect();
+        let inserts_schema = Arc::new(Schema::new(data.clone()));
+        let inserts_partitions = [change_type]
+            .iter()
+            .chain(&commit)
+            .chain(&partitions)
+            .cloned()
+            .collect();
+        let insert = ScalarValue::Utf8(Some("insert".to_string()));
+
+        let scan = |file_schema: SchemaRef, table_partition_cols, files, change_type| {
+            Ok::<_, UnityError>(FileScanConfig {
+                object_store_url: log_store.object_store_url(),
+                statistics: Statistics::new_unknown(&file_schema),
+                file_groups: file_groups(files, change_type, &partitions)
+                    .map_err(|e| delta(e.into()))?,
+                file_schema,
+                projection: None,
+                limit: None,
+                table_partition_cols,
+                output_ordering: vec![],
+            })
+        };
+        let changes = scan(changes_schema, changes_partitions, changes, None)?;
+        let inserts = scan(inserts_schema, inserts_partitions, inserts, Some(insert))?;
+
+        let schema = Arc::new(Schema::new(
+            data.into_iter()
+                .chain(inserts.table_partition_cols.iter().cloned())
+                .collect::<Vec<_>>(),
+        ));
+        Ok(Self {
+            schema,
+            log_store,
+            changes,
+            inserts,
+        })
+    }
+}
+
+/// List the change data files and the inserted data files of the commits
+/// from `start` up to and including `end`.
+///
+/// The change data feed has to stay enabled for all of these commits.
+async fn list_files(
+    log_store: &LogStoreRef,
+    start: i64,
+    end: i64,
+) -> Result<(Vec<ChangeFile>, Vec<ChangeFile>), DeltaTableError> {
+    let mut changes = Vec::new();
+    let mut inserts = Vec::new();
+    for version in start..=end {
+        let commit = log_store
+            .read_commit_entry(version)
+            .await?
+            .ok_or(DeltaTableError::InvalidVersion(version))?;
+        let actions = get_actions(version, commit).await?;
+
+        let mut timestamp = 0;
+        for action in &actions {
+            match action {
+                Action::Metadata(metadata) => {
+                    let enabled = metadata
+                        .configuration
+                        .get(ENABLE_CHANGE_DATA_FEED)
+                        .and_then(|enabled| enabled.as_deref())
+                        .is_some_and(|enabled| enabled.eq_ignore_ascii_case("true"));
+                    if !enabled {
+                        return Err(DeltaTableError::ChangeDataNotRecorded {
+                            version,
+                            start,
+                            end,
+                        });
+                    }
+                }
+                Action::CommitInfo(commit_info) => {
+                    timestamp = commit_info.timestamp.unwrap_or_default();
+                }
+                _ => {}
+            }
+        }
+
+        let file = |path: &String, size, partition_values: &HashMap<_, _>| ChangeFile {
+            version,
+            timestamp,
+            path: path.clone(),
+            size,
+            partition_values: partition_values.clone(),
+        };
+        let changed: Vec<_> = actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Cdc(cdc) => Some(file(&cdc.path, cdc.size, &cdc.partition_values)),
+                _ => None,
+            })
+            .collect();
+        if changed.is_empty() {
+            inserts.extend(actions.iter().filter_map(|action| match action {
+                Action::Add(add) if add.data_change => {
+                    Some(file(&add.path, add.size, &add.partition_values))
+                }
+                _ => None,
+            }));
+        } else {
+            changes.extend(changed);
+        }
+    }
+    Ok((changes, inserts))
+}
+
+/// Group `files` by their partition values, which are the change type if
+/// given, the commit and the values of the table `partitions`.
+fn file_groups(
+    files: Vec<ChangeFile>,
+    change_type: Option<ScalarValue>,
+    partitions: &[Field],
+) -> Result<Vec<Vec<PartitionedFile>>, DataFusionError> {
+    let mut groups: HashMap<Vec<ScalarValue>, Vec<PartitionedFile>> = HashMap::new();
+    for file in files {
+        let mut values: Vec<_> = change_type.iter().cloned().collect();
+        values.push(ScalarValue::Int64(Some(file.version)));
+        values.push(ScalarValue::TimestampMillisecond(
+            Some(file.timestamp),
+            None,
+        ));
+        for field in partitions {
+            let value = match file.partition_values.get(field.name()) {
+                Some(Some(value)) => {
+                    ScalarValue::try_from_string(value.clone(), field.data_type())?
+                }
+                _ => ScalarValue::try_from(field.data_type())?,
+            };
+            values.push(value);
+        }
+
+        let location =
+            Path::parse(&file.path).map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let file = PartitionedFile {
+            object_meta: ObjectMeta {
+                location,
+                last_modified: chrono::Utc.timestamp_nanos(0),
+                size: file.size as usize,
+                e_tag: None,
+                version: None,
+            },
+            partition_values: values.clone(),
+            range: None,
+            statistics: None,
+            extensions: None,
+        };
+        groups.entry(values).or_default().push(file);
+    }
+    Ok(groups.into_values().collect())
+}
+
+#[async_trait]
+impl TableProvider for TableChanges {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        register_store(state, &self.log_store);
+        let format = ParquetFormat::default();
+        let mut scans = Vec::new();
+        // DataFusion cannot run a Parquet scan without files
+        for config in [&self.changes, &self.inserts] {
+            if config.file_groups.is_empty() {
+                continue;
+            }
+            let config = FileScanConfig {
+                projection: projection.cloned(),
+                limit,
+                ..config.clone()
+            };
+            scans.push(format.create_physical_plan(state, config, None).await?);
+        }
+        if scans.is_empty() {
+            let schema = match projection {
+                Some(projection) => Arc::new(self.schema.project(projection)?),
+                None => self.schema.clone(),
+            };
+            return Ok(Arc::new(EmptyExec::new(schema)));
+        }
+        Ok(Arc::new(UnionExec::new(scans)))
+    }
+}
@@ -0,0 +1,472 @@
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use deltalake::kernel::{DataType as DeltaType, PrimitiveType, StructType};
+use serde_json::Value;
+
+use crate::{
+    client::rest::types::{ColumnInfo, ColumnTypeName},
+    unity::error::UnityError,
+};
+
+/// Columns of a table as registered in Unity Catalog, split into the columns
+/// stored in the data files and the columns encoded in the directory layout.
+///
+/// Types are converted the same way `deltalake` converts a Delta schema, so
+/// the result can be compared with the schema of the transaction log.
+pub(crate) struct Columns {
+    /// Columns stored in the data files, in table order.
+    pub file_schema: Schema,
+    /// Partition columns, in partitioning order.
+    pub partition_columns: Vec<Field>,
+}
+
+impl Columns {
+    /// Convert the column metadata of table `name`.
+    pub fn try_new(name: &str, columns: &[ColumnInfo]) -> Result<Columns, UnityError> {
+        let mut columns: Vec<_> = columns.iter().collect();
+        columns.sort_by_key(|column| column.position);
+
+        let mut fields = Vec::with_capacity(columns.len());
+        let mut partition_columns = Vec::new();
+        for column in columns {
+            let field = arrow_field(name, column)?;
+            match column.partition_index {
+                Some(index) => partition_columns.push((index, field)),
+                None => fields.push(field),
+            }
+        }
+        partition_columns.sort_by_key(|(index, _)| *index);
+
+        Ok(Columns {
+            file_schema: Schema::new(fields),
+            partition_columns: partition_columns
+                .into_iter()
+                .map(|(_, field)| field)
+                .collect(),
+        })
+    }
+
+    /// The schema a Delta table reports to DataFusion: partition columns come
+    /// last and string and binary partition values are dictionary encoded.
+    pub fn delta_schema(&self) -> Schema {
+        let partition_columns =
+            self.partition_columns
+                .iter()
+                .map(|field| match field.data_type() {
+                    DataType::Utf8 | DataType::Binary => {
+                        field.clone().with_data_type(DataType::Dictionary(
+                            Box::new(DataType::UInt16),
+                            Box::new(field.data_type().clone()),
+                        ))
+                    }
+                    _ => field.clone(),
+                });
+        let fields: Vec<_> = self
+            .file_schema
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .chain(partition_columns)
+            .collect();
+        Schema::new(fields)
+    }
+}
+
+/// Describe the columns of a Delta schema the way Unity Catalog registers
+/// them, with `type_text` and `type_json` in Spark's notation.
+pub(crate) fn column_infos(schema: &StructType, partition_columns: &[String]) -> Vec<ColumnInfo> {
+    schema
+        .fields()
+        .enumerate()
+        .map(|(position, field)| ColumnInfo {
+            name: Some(field.name().clone()),
+            type_text: Some(field.data_type().to_string()),
+            type_json: serde_json::to_string(field).ok(),
+            type_name: Some(column_type_name(field.data_type())),
+            type_precision: match field.data_type() {
+                DeltaType::Primitive(PrimitiveType::Decimal(precision, _)) => {
+                    Some(*precision as i32)
+                }
+                _ => None,
+            },
+            type_scale: match field.data_type() {
+                DeltaType::Primitive(PrimitiveType::Decimal(_, scale)) => Some(*scale as i32),
+                _ => None,
+            },
+            position: Some(position as i32),
+            nullable: field.is_nullable(),
+            partition_index: partition_columns
+                .iter()
+                .position(|column| column == field.name())
+                .map(|index| index as i32),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn column_type_name(data_type: &DeltaType) -> ColumnTypeName {
+    match data_type {
+        DeltaType::Primitive(primitive) => match primitive {
+            PrimitiveType::String => ColumnTypeName::String,
+            PrimitiveType::Long => ColumnTypeName::Long,
+            PrimitiveType::Integer => ColumnTypeName::Int,
+            PrimitiveType::Short => ColumnTypeName::Short,
+            PrimitiveType::Byte => ColumnTypeName::Byte,
+            PrimitiveType::Float => ColumnTypeName::Float,
+            PrimitiveType::Double => ColumnTypeName::Double,
+            PrimitiveType::Boolean => ColumnTypeName::Boolean,
+            PrimitiveType::Binary => ColumnTypeName::Binary,
+            PrimitiveType::Date => ColumnTypeName::Date,
+            PrimitiveType::Timestamp => ColumnTypeName::Timestamp,
+            PrimitiveType::TimestampNtz => ColumnTypeName::TimestampNtz,
+            PrimitiveType::Decimal(_, _) => ColumnTypeName::Decimal,
+        },
+        DeltaType::Array(_) => ColumnTypeName::Array,
+        DeltaType::Struct(_) => ColumnTypeName::Struct,
+        DeltaType::Map(_) => ColumnTypeName::Map,
+    }
+}
+
+/// Why a column type could not be converted.
+enum TypeError {
+    Unsupported(String),
+    Invalid(String),
+}
+
+fn arrow_field(table_name: &str, column: &ColumnInfo) -> Result<Field, UnityError> {
+    let name = column
+        .name
+        .as_deref()
+        .ok_or_else(|| UnityError::InvalidResponse {
+            name: table_name.to_string(),
+            message: "column is missing 'name'".to_string(),
+        })?;
+
+    let data_type = column_type(column).map_err(|e| match e {
+        TypeError::Unsupported(message) => UnityError::Unsupported {
+            name: table_name.to_string(),
+            message: format!("column '{}' has {}", name, message),
+        },
+        TypeError::Invalid(message) => UnityError::InvalidResponse {
+            name: table_name.to_string(),
+            message: format!("column '{}' has {}", name, message),
+        },
+    })?;
+
+    Ok(Field::new(name, data_type, column.nullable))
+}
+
+fn column_type(column: &ColumnInfo) -> Result<DataType, TypeError> {
+    let Some(type_name) = column.type_name else {
+        return Err(TypeError::Invalid("no 'type_name'".to_string()));
+    };
+
+    let data_type = match type_name {
+        ColumnTypeName::Boolean => DataType::Boolean,
+        ColumnTypeName::Byte => DataType::Int8,
+        ColumnTypeName::Short => DataType::Int16,
+        ColumnTypeName::Int => DataType::Int32,
+        ColumnTypeName::Long => DataType::Int64,
+        ColumnTypeName::Float => DataType::Float32,
+        ColumnTypeName::Double => DataType::Float64,
+        ColumnTypeName::Date => DataType::Date32,
+        ColumnTypeName::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        ColumnTypeName::TimestampNtz => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ColumnTypeName::String | ColumnTypeName::Char => DataType::Utf8,
+        ColumnTypeName::Binary => DataType::Binary,
+        ColumnTypeName::Decimal => match (column.type_precision, column.type_scale) {
+            (Some(precision), Some(scale)) => DataType::Decimal128(precision as u8, scale as i8),
+            _ => column
+                .type_text
+                .as_deref()
+                .map(primitive_type)
+                .unwrap_or(Ok(DataType::Decimal128(10, 0)))?,
+        },
+        ColumnTypeName::Interval => interval_type(
+            column
+                .type_interval_type
+                .as_deref()
+                .or(column.type_text.as_deref())
+                .unwrap_or_default(),
+        ),
+        ColumnTypeName::Null => DataType::Null,
+        ColumnTypeName::Array | ColumnTypeName::Struct | ColumnTypeName::Map => {
+            let Some(type_json) = column.type_json.as_deref() else {
+                return Err(TypeError::Invalid(format!(
+                    "type {:?} without 'type_json'",
+                    type_name
+                )));
+            };
+            let field: Value = serde_json::from_str(type_json)
+                .map_err(|e| TypeError::Invalid(format!("invalid 'type_json': {}", e)))?;
+            let data_type = field
+                .get("type")
+                .ok_or_else(|| TypeError::Invalid("no type in 'type_json'".to_string()))?;
+            spark_type(data_type)?
+        }
+        other => {
+            return Err(TypeError::Unsupported(format!(
+                "unsupported type {:?}",
+                other
+            )))
+        }
+    };
+
+    Ok(data_type)
+}
+
+/// Convert a data type in the JSON format Spark uses for `type_json`.
+fn spark_type(value: &Value) -> Result<DataType, TypeError> {
+    let object = match value {
+        Value::String(name) => return primitive_type(name),
+        Value::Object(object) => object,
+        other => return Err(TypeError::Invalid(format!("invalid type {}", other))),
+    };
+    let child = |key: &str| {
+        object
+            .get(key)
+            .ok_or_else(|| TypeError::Invalid(format!("type without '{}'", key)))
+    };
+    let flag = |key: &str| object.get(key).and_then(Value::as_bool).unwrap_or(true);
+
+    match object.get("type").and_then(Value::as_str) {
+        Some("array") => Ok(DataType::List(Arc::new(Field::new(
+            "item",
+            spark_type(child("elementType")?)?,
+            flag("containsNull"),
+        )))),
+        Some("map") => {
+            let entries = Field::new(
+                "entries",
+                DataType::Struct(
+                    vec![
+                        Field::new("keys", spark_type(child("keyType")?)?, false),
+                        Field::new(
+                            "values",
+                            spark_type(child("valueType")?)?,
+                            flag("valueContainsNull"),
+                        ),
+                    ]
+                    .into(),
+                ),
+                false,
+            );
+            Ok(DataType::Map(Arc::new(entries), false))
+        }
+        Some("struct") => {
+            let fields = child("fields")?
+                .as_array()
+                .ok_or_else(|| TypeError::Invalid("struct fields are not a list".to_string()))?
+                .iter()
+                .map(|field| {
+                    let name = field.get("name").and_then(Value::as_str).ok_or_else(|| {
+                        TypeError::Invalid("struct field without 'name'".to_string())
+                    })?;
+                    let data_type = field.get("type").ok_or_else(|| {
+                        TypeError::Invalid(format!("struct field '{}' without 'type'", name))
+                    })?;
+                    let nullable = field.get("nullable").and_then(Value::as_bool);
+                    Ok(Field::new(
+                        name,
+                        spark_type(data_type)?,
+                        nullable.unwrap_or(true),
+                    ))
+                })
+                .collect::<Result<Vec<_>, TypeError>>()?;
+            Ok(DataType::Struct(fields.into()))
+        }
+        _ => Err(TypeError::Unsupported(format!(
+            "unsupported type {}",
+            value
+        ))),
+    }
+}
+
+/// Convert a primitive type name such as `long` or `decimal(10,2)`.
+fn primitive_type(name: &str) -> Result<DataType, TypeError> {
+    let name = name.trim().to_lowercase();
+    let data_type = match name.as_str() {
+        "boolean" => DataType::Boolean,
+        "byte" | "tinyint" => DataType::Int8,
+        "short" | "smallint" => DataType::Int16,
+        "integer" | "int" => DataType::Int32,
+        "long" | "bigint" => DataType::Int64,
+        "float" | "real" => DataType::Float32,
+        "double" => DataType::Float64,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "timestamp_ntz" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "string" => DataType::Utf8,
+        "binary" => DataType::Binary,
+        "void" | "null" => DataType::Null,
+        "decimal" => DataType::Decimal128(10, 0),
+        name if name.starts_with("char(") || name.starts_with("varchar(") => DataType::Utf8,
+        name if name.starts_with("interval") => interval_type(name),
+        name if name.starts_with("decimal(") => {
+            let invalid = || TypeError::Invalid(format!("invalid type {}", name));
+            let (precision, scale) = name["decimal(".len()..]
+                .trim_end_matches(')')
+                .split_once(',')
+                .ok_or_else(invalid)?;
+            DataType::Decimal128(
+                precision.trim().parse().map_err(|_| invalid())?,
+                scale.trim().parse().map_err(|_| invalid())?,
+            )
+        }
+        other => {
+            return Err(TypeError::Unsupported(format!(
+                "unsupported type {}",
+                other
+            )))
+        }
+    };
+    Ok(data_type)
+}
+
+/// Year-month intervals map to Arrow intervals, day-time intervals to
+/// durations, matching Spark's Arrow conversion.
+fn interval_type(qualifier: &str) -> DataType {
+    let qualifier = qualifier.to_lowercase();
+    if qualifier.contains("year") || qualifier.contains("month") {
+        DataType::Interval(IntervalUnit::YearMonth)
+    } else {
+        DataType::Duration(TimeUnit::Microsecond)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn columns(columns: Value) -> Vec<ColumnInfo> {
+        serde_json::from_value(columns).unwrap()
+    }
+
+    #[test]
+    fn test_partition_columns_are_split_off() {
+        let columns = columns(json!([
+            { "name": "day", "type_name": "DATE", "position": 2, "nullable": false, "partition_index": 0 },
+            { "name": "amount", "type_name": "DECIMAL", "type_precision": 12, "type_scale": 2, "position": 1, "nullable": true },
+            { "name": "id", "type_name": "LONG", "position": 0, "nullable": false },
+        ]));
+
+        let columns = Columns::try_new("main.default.sales", &columns).unwrap();
+        assert_eq!(
+            columns.file_schema,
+            Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("amount", DataType::Decimal128(12, 2), true),
+            ])
+        );
+        assert_eq!(
+            columns.partition_columns,
+            [Field::new("day", DataType::Date32, false)]
+        );
+    }
+
+    #[test]
+    fn test_nested_types_from_type_json() {
+        let type_json = |data_type: Value| json!({ "name": "c", "type": data_type }).to_string();
+        let columns = columns(json!([
+            {
+                "name": "tags", "type_name": "ARRAY", "position": 0, "nullable": true,
+                "type_json": type_json(json!({ "type": "array", "elementType": "string", "containsNull": false })),
+            },
+            {
+                "name": "scores", "type_name": "MAP", "position": 1, "nullable": true,
+                "type_json": type_json(json!({
+                    "type": "map", "keyType": "string", "valueType": "decimal(5,1)", "valueContainsNull": true,
+                })),
+            },
+            {
+                "name": "address", "type_name": "STRUCT", "position": 2, "nullable": false,
+                "type_json": type_json(json!({ "type": "struct", "fields": [
+                    { "name": "city", "type": "varchar(20)", "nullable": true, "metadata": {} },
+                    { "name": "since", "type": "timestamp_ntz", "nullable": false, "metadata": {} },
+                ]})),
+            },
+            { "name": "ttl", "type_name": "INTERVAL", "type_interval_type": "DAY TO SECOND", "position": 3, "nullable": true },
+            { "name": "code", "type_name": "CHAR", "type_text": "char(3)", "position": 4, "nullable": true },
+        ]));
+
+        let schema = Columns::try_new("main.default.users", &columns)
+            .unwrap()
+            .delta_schema();
+        let entries = Field::new(
+            "entries",
+            DataType::Struct(
+                vec![
+                    Field::new("keys", DataType::Utf8, false),
+                    Field::new("values", DataType::Decimal128(5, 1), true),
+                ]
+                .into(),
+            ),
+            false,
+        );
+        let address = vec![
+            Field::new("city", DataType::Utf8, true),
+            Field::new(
+                "since",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                false,
+            ),
+        ];
+        assert_eq!(
+            schema,
+            Schema::new(vec![
+                Field::new(
+                    "tags",
+                    DataType::List(Arc::new(Field::new("item", DataType::Utf8, false))),
+                    true
+                ),
+                Field::new("scores", DataType::Map(Arc::new(entries), false), true),
+                Field::new("address", DataType::Struct(address.into()), false),
+                Field::new("ttl", DataType::Duration(TimeUnit::Microsecond), true),
+                Field::new("code", DataType::Utf8, true),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_column_infos_round_trip() {
+        let arrow = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("price", DataType::Decimal128(12, 2), true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+            Field::new("day", DataType::Date32, true),
+        ]);
+        let schema = StructType::try_from(&arrow).unwrap();
+
+        let infos = column_infos(&schema, &["day".to_string()]);
+        assert_eq!(infos[0].type_name, Some(ColumnTypeName::Long));
+        assert_eq!(infos[1].type_text.as_deref(), Some("decimal(12,2)"));
+        assert_eq!(infos[2].type_text.as_deref(), Some("array<string>"));
+        assert_eq!(infos[3].partition_index, Some(0));
+
+        let columns = Columns::try_new("main.default.sales", &infos).unwrap();
+        assert_eq!(columns.file_schema.fields().len(), 3);
+        assert_eq!(
+            columns.file_schema.field(2).data_type(),
+            arrow.field(2).data_type()
+        );
+        assert_eq!(columns.partition_columns[0].name(), "day");
+    }
+
+    #[test]
+    fn test_unsupported_column_type() {
+        let columns = columns(json!([
+            { "name": "shape", "type_name": "USER_DEFINED_TYPE", "position": 0, "nullable": true },
+        ]));
+        let Err(err) = Columns::try_new("main.default.shapes", &columns) else {
+            panic!("expected user defined type to be rejected");
+        };
+        assert!(matches!(err, UnityError::Unsupported { .. }));
+    }
+}
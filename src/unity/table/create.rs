@@ -0,0 +1,197 @@
+use std::{collections::HashMap, sync::Arc};
+
+use datafusion::{
+    datasource::{
+        file_format::{avro::AvroFormat, csv::CsvFormat, json::JsonFormat, parquet::ParquetFormat},
+        listing::ListingTable,
+        TableProvider,
+    },
+    prelude::SessionContext,
+};
+use deltalake::{kernel::StructType, protocol::SaveMode, DeltaOps, DeltaTable, DeltaTableError};
+
+use super::columns::column_infos;
+use crate::{
+    client::rest::types::{CreateTable, DataSourceFormat, TableType},
+    unity::error::UnityError,
+};
+
+/// The registration of a new table, and the rows to write to it once it is
+/// registered.
+pub(crate) struct PreparedTable {
+    pub request: CreateTable,
+    /// Rows of a managed table, written to its location after registration.
+    pub data: Option<Arc<dyn TableProvider>>,
+}
+
+/// Prepare the registration of table `catalog.schema.name` from the provider
+/// DataFusion passes to `register_table`.
+///
+/// Delta tables and file listings opened by `CREATE EXTERNAL TABLE` are
+/// registered as external tables at their current location. Any other
+/// provider, such as the in-memory table built by `CREATE TABLE` or
+/// `CREATE TABLE .. AS SELECT`, becomes a managed table below
+/// `managed_location`. Its rows are only written with
+/// [`write_managed_table`] once the server has accepted the registration,
+/// so that a rejected table leaves no files behind.
+pub(crate) fn prepare_table(
+    catalog: &str,
+    schema: &str,
+    name: &str,
+    provider: Arc<dyn TableProvider>,
+    managed_location: Option<&str>,
+) -> Result<PreparedTable, UnityError> {
+    let full_name = format!("{}.{}.{}", catalog, schema, name);
+    let request =
+        |table_type, data_source_format, columns, storage_location, properties| CreateTable {
+            name: name.to_string(),
+            catalog_name: catalog.to_string(),
+            schema_name: schema.to_string(),
+            table_type,
+            data_source_format,
+            columns,
+            storage_location,
+            comment: None,
+            properties,
+        };
+    let external = |request| PreparedTable {
+        request,
+        data: None,
+    };
+
+    if let Some(table) = provider.as_any().downcast_ref::<DeltaTable>() {
+        let delta = |e| UnityError::delta(&full_name, e);
+        let columns = column_infos(
+            table.get_schema().map_err(delta)?,
+            &table.metadata().map_err(delta)?.partition_columns,
+        );
+        return Ok(external(request(
+            TableType::External,
+            DataSourceFormat::Delta,
+            columns,
+            table.table_uri(),
+            HashMap::new(),
+        )));
+    }
+
+    if let Some(table) = provider.as_any().downcast_ref::<ListingTable>() {
+        let Some(location) = table.table_paths().first() else {
+            return Err(unsupported(&full_name, "table has no location"));
+        };
+        let (format, properties) = listing_format(&full_name, table)?;
+        let partition_columns: Vec<_> = table
+            .options()
+            .table_partition_cols
+            .iter()
+            .map(|(column, _)| column.clone())
+            .collect();
+        let schema = StructType::try_from(table.schema().as_ref())
+            .map_err(|e| UnityError::delta(&full_name, e.into()))?;
+        return Ok(external(request(
+            TableType::External,
+            format,
+            column_infos(&schema, &partition_columns),
+            location.to_string(),
+            properties,
+        )));
+    }
+
+    let Some(root) = managed_location else {
+        return Err(UnityError::Config {
+            message: format!(
+                "cannot create managed table '{}' without a managed location",
+                full_name
+            ),
+        });
+    };
+    let location = format!(
+        "{}/{}/{}/{}",
+        root.trim_end_matches('/'),
+        catalog,
+        schema,
+        name
+    );
+    let schema = StructType::try_from(provider.schema().as_ref())
+        .map_err(|e| UnityError::delta(&full_name, e.into()))?;
+    Ok(PreparedTable {
+        request: request(
+            TableType::Managed,
+            DataSourceFormat::Delta,
+            column_infos(&schema, &[]),
+            location,
+            HashMap::new(),
+        ),
+        data: Some(provider),
+    })
+}
+
+/// Create a Delta table at `location` holding the rows of `provider`.
+///
+/// The rows are streamed into the first commit of the table, which fails if
+/// a table already exists at `location`. If writing fails, no commit is made
+/// and the location can be written again.
+pub(crate) async fn write_managed_table(
+    location: &str,
+    provider: Arc<dyn TableProvider>,
+    storage_options: &HashMap<String, String>,
+) -> Result<DeltaTable, DeltaTableError> {
+    let ctx = SessionContext::new();
+    let plan = ctx.read_table(provider)?.create_physical_plan().await?;
+    DeltaOps::try_from_uri_with_storage_options(location, storage_options.clone())
+        .await?
+        .write(vec![])
+        .with_input_execution_plan(plan)
+        .with_input_session_state(ctx.state())
+        .with_save_mode(SaveMode::ErrorIfExists)
+        .await
+}
+
+/// The Unity format of a file listing, with the table properties that
+/// `listing_table` reads back when the table is opened.
+fn listing_format(
+    name: &str,
+    table: &ListingTable,
+) -> Result<(DataSourceFormat, HashMap<String, String>), UnityError> {
+    let format = table.options().format.as_any();
+    let mut properties = HashMap::new();
+    let compression = |variant: &dyn ToString| variant.to_string().to_lowercase();
+
+    if format.is::<ParquetFormat>() {
+        Ok((DataSourceFormat::Parquet, properties))
+    } else if let Some(csv) = format.downcast_ref::<CsvFormat>() {
+        properties.insert(
+            "header".to_string(),
+            csv.has_header().unwrap_or_default().to_string(),
+        );
+        properties.insert(
+            "delimiter".to_string(),
+            char::from(csv.delimiter()).to_string(),
+        );
+        if csv.options().compression.is_compressed() {
+            properties.insert(
+                "compression".to_string(),
+                compression(&csv.options().compression),
+            );
+        }
+        Ok((DataSourceFormat::Csv, properties))
+    } else if let Some(json) = format.downcast_ref::<JsonFormat>() {
+        if json.options().compression.is_compressed() {
+            properties.insert(
+                "compression".to_string(),
+                compression(&json.options().compression),
+            );
+        }
+        Ok((DataSourceFormat::Json, properties))
+    } else if format.is::<AvroFormat>() {
+        Ok((DataSourceFormat::Avro, properties))
+    } else {
+        Err(unsupported(name, "file format cannot be registered"))
+    }
+}
+
+fn unsupported(name: &str, message: &str) -> UnityError {
+    UnityError::Unsupported {
+        name: name.to_string(),
+        message: message.to_string(),
+    }
+}
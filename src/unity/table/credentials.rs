@@ -0,0 +1,429 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use deltalake::storage::ObjectStoreRef;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::{
+    client::{
+        rest::types::{TableOperation, TemporaryCredentials},
+        Table, UnityClient,
+    },
+    unity::error::UnityError,
+};
+
+/// Credentials are requested again once they expire within this margin, so
+/// that requests already in flight do not fail half way through a scan.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Short-lived storage credentials for a single table, vended by Unity Catalog
+/// and requested again shortly before they expire.
+pub(crate) struct VendedCredentials {
+    client: Arc<UnityClient>,
+    table: Table,
+    operation: TableOperation,
+    current: Mutex<Option<TemporaryCredentials>>,
+}
+
+impl VendedCredentials {
+    pub fn new(client: Arc<UnityClient>, table: Table, operation: TableOperation) -> Self {
+        Self {
+            client,
+            table,
+            operation,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Credentials for the same table that allow `operation` instead.
+    pub fn for_operation(&self, operation: TableOperation) -> Self {
+        Self::new(self.client.clone(), self.table.clone(), operation)
+    }
+
+    /// Return the current credentials, requesting new ones if they are about to expire.
+    pub async fn get(&self) -> Result<TemporaryCredentials, UnityError> {
+        let mut current = self.current.lock().await;
+        if let Some(credentials) = current.as_ref().filter(|c| !expires_soon(c)) {
+            return Ok(credentials.clone());
+        }
+
+        tracing::debug!(
+            "requesting {:?} credentials for '{}'",
+            self.operation,
+            self.table.full_name()
+        );
+        let credentials = self
+            .client
+            .temporary_table_credentials(&self.table, self.operation)
+            .await?;
+        *current = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+impl fmt::Debug for VendedCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VendedCredentials")
+            .field("table", &self.table.full_name())
+            .field("operation", &self.operation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether the table at `location` is stored remotely, as credentials are
+/// only vended for remote storage. Local paths are accessed directly.
+pub(crate) fn is_remote(location: &str) -> bool {
+    Url::parse(location).is_ok_and(|url| url.scheme() != "file")
+}
+
+/// When `credentials` expire, or `None` if the server did not say.
+pub(crate) fn expires_at(credentials: &TemporaryCredentials) -> Option<SystemTime> {
+    let millis = u64::try_from(credentials.expiration_time?).unwrap_or_default();
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+/// Whether `credentials` expire within [`REFRESH_MARGIN`].
+pub(crate) fn expires_soon(credentials: &TemporaryCredentials) -> bool {
+    expires_at(credentials).is_some_and(is_expiring)
+}
+
+/// Whether `expires_at` is less than [`REFRESH_MARGIN`] away.
+pub(crate) fn is_expiring(expires_at: SystemTime) -> bool {
+    expires_at
+        .duration_since(SystemTime::now())
+        .map_or(true, |remaining| remaining < REFRESH_MARGIN)
+}
+
+/// Translate vended credentials into `deltalake` storage options.
+///
+/// GCS has no storage option for an OAuth token; those credentials are only
+/// used by the stores built by [`object_store`].
+pub(crate) fn storage_options(credentials: &TemporaryCredentials) -> HashMap<String, String> {
+    let mut options = HashMap::new();
+    if let Some(aws) = &credentials.aws_temp_credentials {
+        options.insert("aws_access_key_id".to_string(), aws.access_key_id.clone());
+        options.insert(
+            "aws_secret_access_key".to_string(),
+            aws.secret_access_key.clone(),
+        );
+        if let Some(token) = &aws.session_token {
+            options.insert("aws_session_token".to_string(), token.clone());
+        }
+    }
+    if let Some(azure) = &credentials.azure_user_delegation_sas {
+        options.insert(
+            "azure_storage_sas_token".to_string(),
+            azure.sas_token.clone(),
+        );
+    }
+    options
+}
+
+/// Build an object store rooted at `location` that asks `credentials` for
+/// fresh credentials whenever the current ones are about to expire.
+///
+/// Returns `None` if support for the storage system of `location` was not
+/// compiled in, in which case callers fall back to [`storage_options`].
+#[cfg_attr(
+    not(any(feature = "s3", feature = "azure", feature = "gcs")),
+    allow(unused_variables)
+)]
+pub(crate) fn object_store(
+    name: &str,
+    location: &Url,
+    credentials: &Arc<VendedCredentials>,
+) -> Result<Option<ObjectStoreRef>, UnityError> {
+    let store: Option<deltalake::storage::object_store::Result<ObjectStoreRef>> = match location
+        .scheme()
+    {
+        #[cfg(feature = "s3")]
+        "s3" | "s3a" => Some(providers::s3(location, credentials)),
+        #[cfg(feature = "azure")]
+        "az" | "adl" | "azure" | "abfs" | "abfss" => Some(providers::azure(location, credentials)),
+        #[cfg(feature = "gcs")]
+        "gs" => Some(providers::gcs(location, credentials)),
+        _ => None,
+    };
+    store.transpose().map_err(|e| UnityError::Storage {
+        name: name.to_string(),
+        source: e,
+    })
+}
+
+#[cfg(any(feature = "s3", feature = "azure", feature = "gcs"))]
+mod providers {
+    use std::{
+        fmt,
+        sync::{Arc, Once},
+    };
+
+    use async_trait::async_trait;
+    use deltalake::storage::{
+        object_store::{self, path::Path, CredentialProvider},
+        url_prefix_handler, ObjectStoreRef,
+    };
+    use url::Url;
+
+    use super::VendedCredentials;
+    use crate::{client::rest::types::TemporaryCredentials, unity::error::UnityError};
+
+    /// Register the `deltalake` log stores for the compiled-in storage systems.
+    fn register_handlers() {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            #[cfg(feature = "s3")]
+            deltalake::aws::register_handlers(None);
+            #[cfg(feature = "azure")]
+            deltalake::azure::register_handlers(None);
+            #[cfg(feature = "gcs")]
+            deltalake::gcp::register_handlers(None);
+        });
+    }
+
+    fn rooted_at(store: impl object_store::ObjectStore, location: &Url) -> ObjectStoreRef {
+        register_handlers();
+        url_prefix_handler(store, Path::from(location.path()))
+    }
+
+    /// Adapts [`VendedCredentials`] to the credential provider of one storage system.
+    #[derive(Debug)]
+    struct Vended<T> {
+        credentials: Arc<VendedCredentials>,
+        store: &'static str,
+        extract: fn(TemporaryCredentials) -> Option<T>,
+    }
+
+    #[async_trait]
+    impl<T: fmt::Debug + Send + Sync + 'static> CredentialProvider for Vended<T> {
+        type Credential = T;
+
+        async fn get_credential(&self) -> object_store::Result<Arc<T>> {
+            let error =
+                |source: Box<dyn std::error::Error + Send + Sync>| object_store::Error::Generic {
+                    store: self.store,
+                    source,
+                };
+            let credentials = self
+                .credentials
+                .get()
+                .await
+                .map_err(|e| error(Box::new(e)))?;
+            let credential = (self.extract)(credentials).ok_or_else(|| {
+                error(Box::new(UnityError::InvalidResponse {
+                    name: self.credentials.table.full_name(),
+                    message: format!("no {} credentials were vended", self.store),
+                }))
+            })?;
+            Ok(Arc::new(credential))
+        }
+    }
+
+    #[cfg(feature = "s3")]
+    pub(super) fn s3(
+        location: &Url,
+        credentials: &Arc<VendedCredentials>,
+    ) -> object_store::Result<ObjectStoreRef> {
+        use object_store::aws::{AmazonS3Builder, AwsCredential};
+
+        let provider = Vended {
+            credentials: credentials.clone(),
+            store: "S3",
+            extract: |credentials| {
+                credentials.aws_temp_credentials.map(|aws| AwsCredential {
+                    key_id: aws.access_key_id,
+                    secret_key: aws.secret_access_key,
+                    token: aws.session_token,
+                })
+            },
+        };
+        let store = AmazonS3Builder::from_env()
+            .with_url(location.as_str())
+            .with_credentials(Arc::new(provider))
+            .build()?;
+        Ok(rooted_at(store, location))
+    }
+
+    #[cfg(feature = "azure")]
+    pub(super) fn azure(
+        location: &Url,
+        credentials: &Arc<VendedCredentials>,
+    ) -> object_store::Result<ObjectStoreRef> {
+        use object_store::azure::{AzureCredential, MicrosoftAzureBuilder};
+
+        let provider = Vended {
+            credentials: credentials.clone(),
+            store: "MicrosoftAzure",
+            extract: |credentials| {
+                credentials.azure_user_delegation_sas.map(|azure| {
+                    let query = azure.sas_token.trim_start_matches('?');
+                    let pairs = url::form_urlencoded::parse(query.as_bytes())
+                        .into_owned()
+                        .collect();
+                    AzureCredential::SASToken(pairs)
+                })
+            },
+        };
+        let store = MicrosoftAzureBuilder::from_env()
+            .with_url(location.as_str())
+            .with_credentials(Arc::new(provider))
+            .build()?;
+        Ok(rooted_at(store, location))
+    }
+
+    #[cfg(feature = "gcs")]
+    pub(super) fn gcs(
+        location: &Url,
+        credentials: &Arc<VendedCredentials>,
+    ) -> object_store::Result<ObjectStoreRef> {
+        use object_store::gcp::{GcpCredential, GoogleCloudStorageBuilder};
+
+        let provider = Vended {
+            credentials: credentials.clone(),
+            store: "GCS",
+            extract: |credentials| {
+                credentials.gcp_oauth_token.map(|gcp| GcpCredential {
+                    bearer: gcp.oauth_token,
+                })
+            },
+        };
+        let store = GoogleCloudStorageBuilder::from_env()
+            .with_url(location.as_str())
+            .with_credentials(Arc::new(provider))
+            .build()?;
+        Ok(rooted_at(store, location))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::client::rest::{types::TableInfo, HttpOptions};
+
+    fn table() -> Table {
+        let info: TableInfo = serde_json::from_value(json!({
+            "table_id": "2a1b",
+            "name": "sales",
+            "catalog_name": "main",
+            "schema_name": "default",
+        }))
+        .unwrap();
+        Table::try_from(info).unwrap()
+    }
+
+    fn credentials_response(expiration_time: SystemTime) -> ResponseTemplate {
+        let millis = expiration_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        ResponseTemplate::new(200).set_body_json(json!({
+            "aws_temp_credentials": {
+                "access_key_id": "AKIA",
+                "secret_access_key": "secret",
+                "session_token": "session",
+            },
+            "expiration_time": millis,
+        }))
+    }
+
+    async fn vended(server: &MockServer) -> VendedCredentials {
+        let client =
+            Arc::new(UnityClient::new(&server.uri(), None, HttpOptions::default()).unwrap());
+        VendedCredentials::new(client, table(), TableOperation::Read)
+    }
+
+    #[tokio::test]
+    async fn test_credentials_are_reused_until_they_expire() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .and(body_json(
+                json!({ "table_id": "2a1b", "operation": "READ" }),
+            ))
+            .respond_with(credentials_response(
+                SystemTime::now() + Duration::from_secs(3600),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let vended = vended(&server).await;
+        vended.get().await.unwrap();
+        let credentials = vended.get().await.unwrap();
+        assert_eq!(
+            storage_options(&credentials),
+            HashMap::from([
+                ("aws_access_key_id".to_string(), "AKIA".to_string()),
+                ("aws_secret_access_key".to_string(), "secret".to_string()),
+                ("aws_session_token".to_string(), "session".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_credentials_are_refreshed_before_expiry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/temporary-table-credentials"))
+            .respond_with(credentials_response(SystemTime::now() + REFRESH_MARGIN / 2))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let vended = vended(&server).await;
+        assert!(expires_soon(&vended.get().await.unwrap()));
+        vended.get().await.unwrap();
+    }
+
+    #[test]
+    fn test_azure_storage_options() {
+        let credentials: TemporaryCredentials = serde_json::from_value(json!({
+            "azure_user_delegation_sas": { "sas_token": "sv=2023&sig=abc" },
+        }))
+        .unwrap();
+        assert_eq!(
+            storage_options(&credentials),
+            HashMap::from([(
+                "azure_storage_sas_token".to_string(),
+                "sv=2023&sig=abc".to_string()
+            )])
+        );
+        assert!(!expires_soon(&credentials));
+    }
+
+    #[test]
+    fn test_gcs_credentials_have_no_storage_options() {
+        let credentials: TemporaryCredentials = serde_json::from_value(json!({
+            "gcp_oauth_token": { "oauth_token": "ya29.token" },
+            "expiration_time": 0,
+        }))
+        .unwrap();
+        assert_eq!(
+            credentials.gcp_oauth_token.as_ref().unwrap().oauth_token,
+            "ya29.token"
+        );
+        assert!(storage_options(&credentials).is_empty());
+        assert!(expires_soon(&credentials));
+    }
+
+    #[test]
+    fn test_only_remote_locations_are_remote() {
+        assert!(is_remote("s3://bucket/sales"));
+        assert!(is_remote(
+            "abfss://container@account.dfs.core.windows.net/sales"
+        ));
+        assert!(is_remote("gs://bucket/sales"));
+        assert!(!is_remote("file:///data/sales"));
+        assert!(!is_remote("/data/sales"));
+    }
+}
@@ -1,85 +1,477 @@
-use std::{any::Any, sync::Arc};
+use std::{any::Any, collections::HashSet, sync::Arc};
 
 use async_trait::async_trait;
 use datafusion::{
     arrow::datatypes::SchemaRef,
-    common::{Constraints, Statistics},
+    common::{Statistics, ToDFSchema},
     datasource::{TableProvider, TableType},
     error::DataFusionError,
     execution::context::SessionState,
-    logical_expr::{Expr, LogicalPlan, TableProviderFilterPushDown},
-    physical_plan::ExecutionPlan,
+    logical_expr::{utils::conjunction, Expr, TableProviderFilterPushDown},
+    physical_plan::{
+        expressions::Column, filter::FilterExec, insert::DataSinkExec, projection::ProjectionExec,
+        ExecutionPlan,
+    },
+    prelude::DataFrame,
+};
+use deltalake::{
+    logstore::LogStoreRef,
+    operations::{
+        delete::DeleteMetrics,
+        merge::{MergeBuilder, MergeMetrics},
+        update::UpdateMetrics,
+    },
+    DeltaOps, DeltaResult, DeltaTable,
+};
+
+use super::{
+    cache::DeltaTableCache, columns::Columns, credentials::VendedCredentials,
+    pushdown::is_exact_partition_filter, version::TableVersion, write::DeltaSink,
+};
+use crate::{
+    client::{rest::types::TableOperation, Table},
+    unity::error::UnityError,
 };
-use deltalake::DeltaTable;
 
+/// A Delta table registered in Unity Catalog.
+///
+/// The schema is taken from the columns registered in Unity Catalog, so
+/// listing and describing tables does not touch storage. The transaction log
+/// is only read when the table is scanned, at which point the catalog schema
+/// is checked against the log. Filters on the partition columns registered
+/// in Unity Catalog are declared exact where file pruning evaluates them
+/// exactly, so DataFusion does not filter the rows again.
+///
+/// A provider pinned to a historical version takes its schema from that
+/// version of the log instead, and cannot be written to.
 pub struct UnityDeltaTable {
-    table: DeltaTable,
+    name: String,
+    location: String,
+    schema: SchemaRef,
+    partition_columns: Vec<String>,
+    tables: Arc<DeltaTableCache>,
+    credentials: Option<Arc<VendedCredentials>>,
+    version: Option<i64>,
 }
 
 impl UnityDeltaTable {
-    pub async fn new(path: &str) -> Self {
-        let table = deltalake::open_table(path).await.unwrap();
-        UnityDeltaTable { table }
+    /// Create a provider for `table`, stored at `location`.
+    ///
+    /// If the catalog holds no usable column metadata, the schema is read
+    /// from the transaction log instead.
+    pub async fn try_new(
+        table: &Table,
+        location: &str,
+        tables: Arc<DeltaTableCache>,
+        credentials: Option<Arc<VendedCredentials>>,
+    ) -> Result<Self, UnityError> {
+        let name = table.full_name();
+        let columns = match table.columns() {
+            [] => None,
+            columns => Columns::try_new(&name, columns)
+                .inspect_err(|e| tracing::debug!("using schema of Delta log instead: {}", e))
+                .ok(),
+        };
+        let (schema, partition_columns) = match columns {
+            Some(columns) => {
+                let partition_columns = columns
+                    .partition_columns
+                    .iter()
+                    .map(|field| field.name().clone())
+                    .collect();
+                (Arc::new(columns.delta_schema()), partition_columns)
+            }
+            None => {
+                let delta_table = tables.open(&name, location, credentials.clone()).await?;
+                let partition_columns = delta_table
+                    .metadata()
+                    .map_err(|e| UnityError::delta(&name, e))?
+                    .partition_columns
+                    .clone();
+                (TableProvider::schema(&delta_table), partition_columns)
+            }
+        };
+
+        Ok(UnityDeltaTable {
+            name,
+            location: location.to_string(),
+            schema,
+            partition_columns,
+            tables,
+            credentials,
+            version: None,
+        })
     }
-}
 
-#[async_trait]
-impl TableProvider for UnityDeltaTable {
-    fn as_any(&self) -> &dyn Any {
-        TableProvider::as_any(&self.table)
+    /// Create a provider for `table` as of `version`, stored at `location`.
+    ///
+    /// A timestamp is resolved to the version that was current at that time,
+    /// so every scan of the provider reads the same snapshot.
+    pub async fn try_new_at(
+        table: &Table,
+        location: &str,
+        tables: Arc<DeltaTableCache>,
+        credentials: Option<Arc<VendedCredentials>>,
+        version: TableVersion,
+    ) -> Result<Self, UnityError> {
+        let name = table.full_name();
+        let delta_table = tables
+            .open_version(&name, location, credentials.clone(), version)
+            .await?;
+
+        let partition_columns = delta_table
+            .metadata()
+            .map_err(|e| UnityError::delta(&name, e))?
+            .partition_columns
+            .clone();
+
+        Ok(UnityDeltaTable {
+            name,
+            location: location.to_string(),
+            schema: TableProvider::schema(&delta_table),
+            partition_columns,
+            tables,
+            credentials,
+            version: Some(delta_table.version()),
+        })
     }
 
-    fn schema(&self) -> SchemaRef {
-        TableProvider::schema(&self.table)
+    /// Open the snapshot of the table this provider reads, which is the
+    /// latest one unless the provider is pinned to a version.
+    pub async fn snapshot(&self) -> Result<DeltaTable, UnityError> {
+        let credentials = self.credentials.clone();
+        match self.version {
+            None => {
+                self.tables
+                    .open(&self.name, &self.location, credentials)
+                    .await
+            }
+            Some(version) => {
+                let version = TableVersion::Version(version);
+                self.tables
+                    .open_version(&self.name, &self.location, credentials, version)
+                    .await
+            }
+        }
     }
 
-    fn constraints(&self) -> Option<&Constraints> {
-        TableProvider::constraints(&self.table)
+    /// Fail if the provider is pinned to a historical version.
+    fn ensure_writable(&self) -> Result<(), UnityError> {
+        match self.version {
+            None => Ok(()),
+            Some(version) => Err(UnityError::Unsupported {
+                name: self.name.clone(),
+                message: format!("version {} is read-only", version),
+            }),
+        }
     }
 
-    fn table_type(&self) -> TableType {
-        TableType::Base
+    /// Read-write credentials for commits, if credentials are vended.
+    fn write_credentials(&self) -> Option<Arc<VendedCredentials>> {
+        self.credentials
+            .as_ref()
+            .map(|credentials| Arc::new(credentials.for_operation(TableOperation::ReadWrite)))
     }
 
-    fn get_table_definition(&self) -> Option<&str> {
-        TableProvider::get_table_definition(&self.table)
+    /// Open the latest snapshot of the table for a commit made by an
+    /// operation that runs in `state`.
+    async fn open_for_write(&self, state: &SessionState) -> Result<DeltaTable, UnityError> {
+        self.ensure_writable()?;
+        let table = self
+            .tables
+            .open_for_write(&self.name, &self.location, self.write_credentials())
+            .await?;
+        register_store(state, &table.log_store());
+        Ok(table)
     }
 
-    fn get_logical_plan(&self) -> Option<&LogicalPlan> {
-        TableProvider::get_logical_plan(&self.table)
+    /// Delete the rows matching `predicate`, or all rows.
+    pub(crate) async fn delete(
+        &self,
+        state: SessionState,
+        predicate: Option<Expr>,
+    ) -> Result<DeleteMetrics, UnityError> {
+        let mut builder = DeltaOps(self.open_for_write(&state).await?)
+            .delete()
+            .with_session_state(state);
+        if let Some(predicate) = predicate {
+            builder = builder.with_predicate(predicate);
+        }
+        let (_, metrics) = builder
+            .await
+            .map_err(|e| UnityError::delta(&self.name, e))?;
+        self.tables.invalidate(&self.location);
+        Ok(metrics)
     }
 
-    fn get_column_default(&self, column: &str) -> Option<&Expr> {
-        TableProvider::get_column_default(&self.table, column)
+    /// Set columns to new values in the rows matching `predicate`, or in all rows.
+    pub(crate) async fn update(
+        &self,
+        state: SessionState,
+        assignments: Vec<(String, Expr)>,
+        predicate: Option<Expr>,
+    ) -> Result<UpdateMetrics, UnityError> {
+        let mut builder = DeltaOps(self.open_for_write(&state).await?)
+            .update()
+            .with_session_state(state);
+        for (column, value) in assignments {
+            builder = builder.with_update(column, value);
+        }
+        if let Some(predicate) = predicate {
+            builder = builder.with_predicate(predicate);
+        }
+        let (_, metrics) = builder
+            .await
+            .map_err(|e| UnityError::delta(&self.name, e))?;
+        self.tables.invalidate(&self.location);
+        Ok(metrics)
     }
 
-    async fn scan(
+    /// Merge `source` into the table, joining on `predicate`. The clauses of
+    /// the merge are added by `clauses`.
+    pub(crate) async fn merge(
+        &self,
+        state: SessionState,
+        source: DataFrame,
+        predicate: String,
+        clauses: impl FnOnce(MergeBuilder) -> DeltaResult<MergeBuilder>,
+    ) -> Result<MergeMetrics, UnityError> {
+        let delta = |e| UnityError::delta(&self.name, e);
+        let builder = DeltaOps(self.open_for_write(&state).await?)
+            .merge(source, predicate)
+            .with_session_state(state);
+        let (_, metrics) = clauses(builder).map_err(delta)?.await.map_err(delta)?;
+        self.tables.invalidate(&self.location);
+        Ok(metrics)
+    }
+
+    /// Whether `table` is partitioned by the partition columns registered in
+    /// the catalog, which filters are declared exact for.
+    fn partitioned_as_registered(&self, table: &DeltaTable) -> Result<bool, UnityError> {
+        let partition_columns = &table
+            .metadata()
+            .map_err(|e| UnityError::delta(&self.name, e))?
+            .partition_columns;
+        let sorted = |columns: &[String]| {
+            let mut columns = columns.to_vec();
+            columns.sort();
+            columns
+        };
+        if sorted(partition_columns) != sorted(&self.partition_columns) {
+            tracing::warn!(
+                "partition columns of '{}' are {:?} in the catalog but {:?} in the Delta log",
+                self.name,
+                self.partition_columns,
+                partition_columns
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Scan `table` with the `exact` filters, which DataFusion does not
+    /// apply again once they are pushed down.
+    ///
+    /// The scan reads the columns of the filters as well, since `deltalake`
+    /// only prunes on filters over scanned columns, and applies the filters
+    /// to the scanned rows when pruning files on them is not exact.
+    async fn scan_exact(
         &self,
+        table: &DeltaTable,
         state: &SessionState,
-        projection: Option<&Vec<usize>>,
+        projection: Vec<usize>,
         filters: &[Expr],
+        exact: Vec<Expr>,
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
-        TableProvider::scan(&self.table, state, projection, filters, limit).await
+        let delta_schema = TableProvider::schema(table);
+        let mut scanned = projection.clone();
+        let mut columns = HashSet::new();
+        for filter in &exact {
+            for column in filter.to_columns()? {
+                let index = delta_schema.index_of(&column.name)?;
+                if !scanned.contains(&index) {
+                    scanned.push(index);
+                }
+                columns.insert(column.name);
+            }
+        }
+
+        let prunes_exactly = self.partitioned_as_registered(table)?
+            && self
+                .tables
+                .inexact_partition_columns(&self.location, table)
+                .map_err(|e| UnityError::delta(&self.name, e))?
+                .is_disjoint(&columns);
+        let plan = if prunes_exactly {
+            TableProvider::scan(table, state, Some(&scanned), filters, limit).await?
+        } else {
+            tracing::debug!("filtering rows of '{}' after pruning files", self.name);
+            let plan = TableProvider::scan(table, state, Some(&scanned), filters, None).await?;
+            let schema = plan.schema().to_dfschema()?;
+            let predicate = conjunction(exact).expect("exact filters are not empty");
+            let predicate = state.create_physical_expr(predicate, &schema)?;
+            Arc::new(FilterExec::try_new(predicate, plan)?)
+        };
+        if scanned.len() == projection.len() {
+            return Ok(plan);
+        }
+
+        let columns = (0..projection.len())
+            .map(|index| {
+                let name = plan.schema().field(index).name().clone();
+                (Arc::new(Column::new(&name, index)) as _, name)
+            })
+            .collect();
+        Ok(Arc::new(ProjectionExec::try_new(columns, plan)?))
     }
 
-    fn supports_filters_pushdown(
+    /// Map a projection of the catalog schema onto the schema of `table`,
+    /// failing if a projected column is missing or has changed type.
+    fn project(
         &self,
-        filters: &[&Expr],
-    ) -> Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
-        TableProvider::supports_filters_pushdown(&self.table, filters)
+        table: &DeltaTable,
+        projection: Option<&Vec<usize>>,
+    ) -> Result<Vec<usize>, UnityError> {
+        let delta_schema = TableProvider::schema(table);
+        let all: Vec<_> = (0..self.schema.fields().len()).collect();
+        projection
+            .unwrap_or(&all)
+            .iter()
+            .map(|&index| {
+                let field = self.schema.field(index);
+                let drift = |message: String| UnityError::SchemaMismatch {
+                    name: self.name.clone(),
+                    message,
+                };
+                let (delta_index, delta_field) = delta_schema
+                    .column_with_name(field.name())
+                    .ok_or_else(|| drift(format!("column '{}' is missing", field.name())))?;
+                if !delta_field.data_type().equals_datatype(field.data_type()) {
+                    return Err(drift(format!(
+                        "column '{}' has type {} in the catalog but {} in the Delta log",
+                        field.name(),
+                        field.data_type(),
+                        delta_field.data_type()
+                    )));
+                }
+                Ok(delta_index)
+            })
+            .collect()
     }
+}
 
+/// Register the store of a Delta table with `state`, so that plans built by
+/// `deltalake` can be run in it.
+///
+/// `deltalake` only registers the store with the sessions it creates itself.
+pub(crate) fn register_store(state: &SessionState, log_store: &LogStoreRef) {
+    state.runtime_env().register_object_store(
+        log_store.object_store_url().as_ref(),
+        log_store.object_store(),
+    );
+}
+
+#[async_trait]
+impl TableProvider for UnityDeltaTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    /// Statistics from the log of the snapshot a previous query opened.
+    ///
+    /// DataFusion asks for statistics while planning, which must not block on
+    /// reading the log, so there are none until the table was scanned once.
+    /// Delta tables declare no primary key or unique constraints, so
+    /// `constraints` is not overridden.
     fn statistics(&self) -> Option<Statistics> {
-        TableProvider::statistics(&self.table)
+        let table = self.tables.cached(&self.location, self.version)?;
+        let snapshot = table.snapshot().ok()?;
+        let statistics = snapshot.datafusion_table_statistics()?;
+        // the log reports columns in the order of the Delta schema
+        let delta_columns: Vec<_> = snapshot.schema().fields().map(|f| f.name()).collect();
+        let column_statistics = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let index = delta_columns
+                    .iter()
+                    .position(|name| *name == field.name())?;
+                Some(statistics.column_statistics[index].clone())
+            })
+            .collect::<Option<_>>()?;
+        Some(Statistics {
+            column_statistics,
+            ..statistics
+        })
     }
 
-    async fn insert_into(
+    async fn scan(
         &self,
         state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let table = self.snapshot().await?;
+        let projection = self.project(&table, projection)?;
+
+        let exact: Vec<_> = filters
+            .iter()
+            .filter(|filter| is_exact_partition_filter(filter, &self.partition_columns))
+            .cloned()
+            .collect();
+        if exact.is_empty() {
+            return TableProvider::scan(&table, state, Some(&projection), filters, limit).await;
+        }
+        self.scan_exact(&table, state, projection, filters, exact, limit)
+            .await
+    }
+
+    async fn insert_into(
+        &self,
+        _state: &SessionState,
         input: Arc<dyn ExecutionPlan>,
         overwrite: bool,
     ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
-        TableProvider::insert_into(&self.table, state, input, overwrite).await
+        self.ensure_writable()?;
+        let sink = DeltaSink::new(
+            self.name.clone(),
+            self.location.clone(),
+            self.tables.clone(),
+            self.write_credentials(),
+            overwrite,
+        );
+        Ok(Arc::new(DataSinkExec::new(
+            input,
+            Arc::new(sink),
+            self.schema.clone(),
+            None,
+        )))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
+        Ok(filters
+            .iter()
+            .map(|filter| {
+                if is_exact_partition_filter(filter, &self.partition_columns) {
+                    TableProviderFilterPushDown::Exact
+                } else {
+                    TableProviderFilterPushDown::Inexact
+                }
+            })
+            .collect())
     }
 }
@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, future::ready, sync::Arc};
+
+use datafusion::arrow::{
+    array::{
+        ArrayRef, BooleanArray, Int32Array, Int64Array, ListBuilder, MapBuilder, StringArray,
+        StringBuilder, TimestampMillisecondArray,
+    },
+    record_batch::RecordBatch,
+};
+use deltalake::{
+    kernel::{Action, CommitInfo},
+    logstore::get_actions,
+    DeltaTable,
+};
+use futures::{StreamExt, TryStreamExt};
+use serde_json::Value;
+
+use crate::unity::error::UnityError;
+
+/// Commit files read concurrently while collecting the history.
+const CONCURRENT_READS: usize = 16;
+
+/// The commits of `table`, latest first, as returned by `describe_history`.
+///
+/// Lists the latest `limit` commits, or all commits that are still in the
+/// log. Every commit has a `version`, `timestamp`, `operation`,
+/// `operationParameters` and `operationMetrics`, and the other fields of the
+/// commit info that Delta writers record.
+pub(crate) async fn history(
+    name: &str,
+    table: &DeltaTable,
+    limit: Option<usize>,
+) -> Result<RecordBatch, UnityError> {
+    let commits = commits(name, table, limit).await?;
+    let info = |commit: &Option<CommitInfo>| commit.clone().unwrap_or_default();
+    let infos: Vec<_> = commits.iter().map(|(_, commit)| info(commit)).collect();
+
+    let columns: Vec<(&str, ArrayRef)> = vec![
+        (
+            "version",
+            Arc::new(Int64Array::from_iter_values(
+                commits.iter().map(|(version, _)| *version),
+            )),
+        ),
+        (
+            "timestamp",
+            Arc::new(
+                TimestampMillisecondArray::from_iter(infos.iter().map(|info| info.timestamp))
+                    .with_timezone("UTC"),
+            ),
+        ),
+        (
+            "userId",
+            Arc::new(StringArray::from_iter(
+                infos.iter().map(|info| info.user_id.as_deref()),
+            )),
+        ),
+        (
+            "userName",
+            Arc::new(StringArray::from_iter(
+                infos.iter().map(|info| info.user_name.as_deref()),
+            )),
+        ),
+        (
+            "operation",
+            Arc::new(StringArray::from_iter(
+                infos.iter().map(|info| info.operation.as_deref()),
+            )),
+        ),
+        (
+            "operationParameters",
+            string_map(
+                infos
+                    .iter()
+                    .map(|info| info.operation_parameters.as_ref().map(json_strings)),
+            ),
+        ),
+        (
+            "readVersion",
+            Arc::new(Int64Array::from_iter(
+                infos.iter().map(|info| info.read_version),
+            )),
+        ),
+        (
+            "isolationLevel",
+            Arc::new(StringArray::from_iter(infos.iter().map(|info| {
+                info.isolation_level
+                    .as_ref()
+                    .map(|level| level.as_ref().to_string())
+            }))),
+        ),
+        (
+            "isBlindAppend",
+            Arc::new(BooleanArray::from_iter(
+                infos.iter().map(|info| info.is_blind_append),
+            )),
+        ),
+        (
+            "operationMetrics",
+            string_map(
+                infos
+                    .iter()
+                    .map(|info| match info.info.get("operationMetrics") {
+                        Some(Value::Object(metrics)) => Some(json_strings(metrics)),
+                        _ => None,
+                    }),
+            ),
+        ),
+        (
+            "engineInfo",
+            Arc::new(StringArray::from_iter(
+                infos.iter().map(|info| info.engine_info.as_deref()),
+            )),
+        ),
+    ];
+    RecordBatch::try_from_iter(columns).map_err(|e| UnityError::delta(name, e.into()))
+}
+
+/// A single row describing the current snapshot of `table`, as returned by
+/// `describe_detail`.
+pub(crate) async fn detail(name: &str, table: &DeltaTable) -> Result<RecordBatch, UnityError> {
+    let delta = |e| UnityError::delta(name, e);
+    let snapshot = table.snapshot().map_err(delta)?;
+    let metadata = snapshot.metadata();
+    let protocol = snapshot.protocol();
+    let size: i64 = snapshot
+        .file_actions_iter()
+        .map_err(delta)?
+        .map(|add| add.size)
+        .sum();
+    let last_modified = commits(name, table, Some(1))
+        .await?
+        .first()
+        .and_then(|(_, commit)| commit.as_ref())
+        .and_then(|commit| commit.timestamp);
+
+    let mut partition_columns = ListBuilder::new(StringBuilder::new());
+    partition_columns.append_value(metadata.partition_columns.iter().map(Some));
+    let properties = metadata
+        .configuration
+        .iter()
+        .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+        .collect();
+
+    let columns: Vec<(&str, ArrayRef)> = vec![
+        ("format", Arc::new(StringArray::from(vec!["delta"]))),
+        ("id", Arc::new(StringArray::from(vec![metadata.id.clone()]))),
+        ("name", Arc::new(StringArray::from(vec![name]))),
+        (
+            "description",
+            Arc::new(StringArray::from(vec![metadata.description.clone()])),
+        ),
+        (
+            "location",
+            Arc::new(StringArray::from(vec![table.table_uri()])),
+        ),
+        (
+            "createdAt",
+            Arc::new(
+                TimestampMillisecondArray::from(vec![metadata.created_time]).with_timezone("UTC"),
+            ),
+        ),
+        (
+            "lastModified",
+            Arc::new(TimestampMillisecondArray::from(vec![last_modified]).with_timezone("UTC")),
+        ),
+        ("version", Arc::new(Int64Array::from(vec![table.version()]))),
+        ("partitionColumns", Arc::new(partition_columns.finish())),
+        (
+            "numFiles",
+            Arc::new(Int64Array::from(vec![snapshot.files_count() as i64])),
+        ),
+        ("sizeInBytes", Arc::new(Int64Array::from(vec![size]))),
+        ("properties", string_map([Some(properties)].into_iter())),
+        (
+            "minReaderVersion",
+            Arc::new(Int32Array::from(vec![protocol.min_reader_version])),
+        ),
+        (
+            "minWriterVersion",
+            Arc::new(Int32Array::from(vec![protocol.min_writer_version])),
+        ),
+    ];
+    RecordBatch::try_from_iter(columns).map_err(|e| UnityError::delta(name, e.into()))
+}
+
+/// The latest `limit` commits of `table`, or all commits still in the log,
+/// with their commit info if the writer recorded one.
+async fn commits(
+    name: &str,
+    table: &DeltaTable,
+    limit: Option<usize>,
+) -> Result<Vec<(i64, Option<CommitInfo>)>, UnityError> {
+    let log_store = table.log_store();
+    let latest = table.version();
+    let oldest = limit.map_or(0, |limit| (latest + 1 - limit as i64).max(0));
+
+    futures::stream::iter((oldest..=latest).rev())
+        .map(|version| {
+            let log_store = log_store.clone();
+            async move { Ok((version, log_store.read_commit_entry(version).await?)) }
+        })
+        .buffered(CONCURRENT_READS)
+        // older commits have been cleaned up
+        .try_take_while(|(_, commit)| ready(Ok(commit.is_some())))
+        .and_then(|(version, commit)| async move {
+            let actions = get_actions(version, commit.unwrap_or_default()).await?;
+            let info = actions.into_iter().find_map(|action| match action {
+                Action::CommitInfo(info) => Some(info),
+                _ => None,
+            });
+            Ok((version, info))
+        })
+        .try_collect()
+        .await
+        .map_err(|e| UnityError::delta(name, e))
+}
+
+/// A map array with string keys and values.
+fn string_map(maps: impl Iterator<Item = Option<BTreeMap<String, String>>>) -> ArrayRef {
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+    for map in maps {
+        let Some(map) = map else {
+            builder
+                .append(false)
+                .expect("keys and values are appended together");
+            continue;
+        };
+        for (key, value) in map {
+            builder.keys().append_value(key);
+            builder.values().append_value(value);
+        }
+        builder
+            .append(true)
+            .expect("keys and values are appended together");
+    }
+    Arc::new(builder.finish())
+}
+
+/// The entries of a JSON object, rendering values that are not strings as JSON.
+fn json_strings<'a>(
+    entries: impl IntoIterator<Item = (&'a String, &'a Value)>,
+) -> BTreeMap<String, String> {
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
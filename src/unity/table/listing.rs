@@ -0,0 +1,166 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use datafusion::{
+    common::{FileType, GetExt},
+    datasource::{
+        file_format::{
+            avro::AvroFormat,
+            csv::CsvFormat,
+            file_compression_type::{FileCompressionType, FileTypeExt},
+            json::JsonFormat,
+            parquet::ParquetFormat,
+            FileFormat,
+        },
+        listing::{ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl},
+    },
+};
+
+use super::columns::Columns;
+use crate::{
+    client::{rest::types::DataSourceFormat, Table},
+    unity::error::UnityError,
+};
+
+/// Open a table that is stored as plain Parquet, CSV, JSON or Avro files.
+///
+/// The schema is taken from the columns registered in Unity Catalog, and
+/// partition columns are read from Hive-style `column=value` directories.
+/// CSV and JSON files are configured from the table properties `header`,
+/// `delimiter` (or `sep`), `quote`, `escape` and `compression`.
+pub(crate) fn listing_table(
+    table: &Table,
+    format: DataSourceFormat,
+    location: &str,
+) -> Result<ListingTable, UnityError> {
+    let name = table.full_name();
+    let (file_format, file_extension) = file_format(&name, format, table.properties())?;
+    let columns = Columns::try_new(&name, table.columns())?;
+
+    let table_path = if location.ends_with('/') {
+        location.to_string()
+    } else {
+        format!("{}/", location)
+    };
+    let table_path =
+        ListingTableUrl::parse(&table_path).map_err(|e| UnityError::InvalidResponse {
+            name: name.clone(),
+            message: format!("invalid storage location '{}': {}", location, e),
+        })?;
+
+    let partition_columns = columns
+        .partition_columns
+        .into_iter()
+        .map(|field| (field.name().clone(), field.data_type().clone()))
+        .collect();
+    let options = ListingOptions::new(file_format)
+        .with_file_extension(file_extension)
+        .with_table_partition_cols(partition_columns);
+    let config = ListingTableConfig::new(table_path)
+        .with_listing_options(options)
+        .with_schema(Arc::new(columns.file_schema));
+
+    ListingTable::try_new(config).map_err(|e| UnityError::InvalidResponse {
+        name,
+        message: e.to_string(),
+    })
+}
+
+fn file_format(
+    name: &str,
+    format: DataSourceFormat,
+    properties: &HashMap<String, String>,
+) -> Result<(Arc<dyn FileFormat>, String), UnityError> {
+    let invalid = |property: &str, value: &str| UnityError::InvalidResponse {
+        name: name.to_string(),
+        message: format!(
+            "invalid value '{}' for table property '{}'",
+            value, property
+        ),
+    };
+    let compression = match properties.get("compression") {
+        Some(value) => {
+            FileCompressionType::from_str(value).map_err(|_| invalid("compression", value))?
+        }
+        None => FileCompressionType::UNCOMPRESSED,
+    };
+    let byte = |property: &str| match properties.get(property) {
+        Some(value) if value.len() == 1 => Ok(Some(value.as_bytes()[0])),
+        Some(value) => Err(invalid(property, value)),
+        None => Ok(None),
+    };
+    let with_compression = |file_type: FileType| {
+        file_type
+            .get_ext_with_compression(compression)
+            .map_err(|e| invalid("compression", &e.to_string()))
+    };
+
+    match format {
+        DataSourceFormat::Parquet => Ok((
+            Arc::new(ParquetFormat::default()),
+            FileType::PARQUET.get_ext(),
+        )),
+        DataSourceFormat::Csv => {
+            let has_header = match properties.get("header") {
+                Some(value) => value
+                    .parse::<bool>()
+                    .map_err(|_| invalid("header", value))?,
+                None => false,
+            };
+            let mut csv = CsvFormat::default()
+                .with_has_header(has_header)
+                .with_file_compression_type(compression);
+            if let Some(delimiter) = byte("delimiter")?.or(byte("sep")?) {
+                csv = csv.with_delimiter(delimiter);
+            }
+            if let Some(quote) = byte("quote")? {
+                csv = csv.with_quote(quote);
+            }
+            if let Some(escape) = byte("escape")? {
+                csv = csv.with_escape(Some(escape));
+            }
+            Ok((Arc::new(csv), with_compression(FileType::CSV)?))
+        }
+        DataSourceFormat::Json => {
+            let json = JsonFormat::default().with_file_compression_type(compression);
+            Ok((Arc::new(json), with_compression(FileType::JSON)?))
+        }
+        DataSourceFormat::Avro => Ok((Arc::new(AvroFormat), FileType::AVRO.get_ext())),
+        other => Err(UnityError::Unsupported {
+            name: name.to_string(),
+            message: format!(
+                "tables stored as {} are not supported",
+                format!("{:?}", other).to_uppercase()
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_formats_are_rejected() {
+        for format in [DataSourceFormat::Orc, DataSourceFormat::Text] {
+            let Err(err) = file_format("main.default.logs", format, &HashMap::new()) else {
+                panic!("expected {:?} to be rejected", format);
+            };
+            assert!(matches!(err, UnityError::Unsupported { .. }));
+        }
+        let err = file_format("main.default.logs", DataSourceFormat::Orc, &HashMap::new())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unsupported table 'main.default.logs': tables stored as ORC are not supported"
+        );
+    }
+
+    #[test]
+    fn test_compressed_csv_extension() {
+        let properties = HashMap::from([("compression".to_string(), "gzip".to_string())]);
+        let (_, extension) =
+            file_format("main.default.logs", DataSourceFormat::Csv, &properties).unwrap();
+        assert_eq!(extension, ".csv.gz");
+    }
+}
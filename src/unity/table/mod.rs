@@ -1 +1,13 @@
+pub mod cache;
+pub mod changes;
+pub mod columns;
+pub mod create;
+pub mod credentials;
 pub mod delta;
+pub mod history;
+pub mod listing;
+pub mod pushdown;
+pub mod unavailable;
+pub mod version;
+pub mod view;
+pub mod write;
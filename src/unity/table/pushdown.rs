@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use datafusion::logical_expr::{expr::InList, BinaryExpr, Expr, Operator};
+use deltalake::{DeltaResult, DeltaTable};
+use serde_json::Value;
+
+/// Longest `IN` list that DataFusion's pruning rewrites into equalities
+/// instead of keeping every file.
+const MAX_IN_LIST: usize = 20;
+
+/// Whether `filter` only compares partition columns with literals in a way
+/// that file pruning evaluates exactly.
+///
+/// Every file holds a single value of each partition column, so pruning on
+/// partition values selects exactly the files whose rows match, provided
+/// the filter has a shape that the pruning predicate understands. Anything
+/// else, such as casts or function calls, may keep files that do not match.
+pub(crate) fn is_exact_partition_filter(filter: &Expr, partition_columns: &[String]) -> bool {
+    let is_partition_column = |expr: &Expr| matches!(expr, Expr::Column(column) if partition_columns.contains(&column.name));
+    let is_literal = |expr: &Expr| matches!(expr, Expr::Literal(value) if !value.is_null());
+
+    match filter {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And | Operator::Or => {
+                is_exact_partition_filter(left, partition_columns)
+                    && is_exact_partition_filter(right, partition_columns)
+            }
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq => {
+                (is_partition_column(left) && is_literal(right))
+                    || (is_literal(left) && is_partition_column(right))
+            }
+            _ => false,
+        },
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) => is_partition_column(expr),
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => is_partition_column(expr) && list.len() <= MAX_IN_LIST && list.iter().all(is_literal),
+        _ => false,
+    }
+}
+
+/// The partition columns of `table` that file pruning cannot evaluate
+/// exactly.
+///
+/// A file with a null partition value can only be pruned if its statistics
+/// record the number of rows, which tells the pruning predicate that all
+/// values are null. Writers usually record it, but are not required to.
+pub(crate) fn inexact_partition_columns(table: &DeltaTable) -> DeltaResult<HashSet<String>> {
+    let snapshot = table.snapshot()?;
+    let partition_columns = &snapshot.metadata().partition_columns;
+    let mut inexact = HashSet::new();
+    for add in snapshot.file_actions_iter()? {
+        let null_columns: Vec<_> = partition_columns
+            .iter()
+            .filter(|column| !inexact.contains(*column))
+            .filter(|column| !matches!(add.partition_values.get(*column), Some(Some(_))))
+            .collect();
+        if !null_columns.is_empty() && !has_num_records(add.stats.as_deref()) {
+            inexact.extend(null_columns.into_iter().cloned());
+            if inexact.len() == partition_columns.len() {
+                break;
+            }
+        }
+    }
+    Ok(inexact)
+}
+
+fn has_num_records(stats: Option<&str>) -> bool {
+    stats
+        .and_then(|stats| serde_json::from_str::<Value>(stats).ok())
+        .is_some_and(|stats| stats.get("numRecords").is_some_and(Value::is_number))
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::prelude::{col, lit};
+
+    use super::*;
+
+    #[test]
+    fn test_exact_partition_filters() {
+        let partition_columns = ["day".to_string(), "region".to_string()];
+        let exact = |filter: Expr| is_exact_partition_filter(&filter, &partition_columns);
+
+        assert!(exact(col("day").eq(lit("2024-01-01"))));
+        assert!(exact(lit(3).lt(col("region"))));
+        assert!(exact(col("day").is_null()));
+        assert!(exact(
+            col("day")
+                .gt_eq(lit("2024-01-01"))
+                .and(col("region").in_list(vec![lit("eu"), lit("us")], false))
+                .or(col("region").is_not_null())
+        ));
+
+        assert!(!exact(col("id").eq(lit(1))));
+        assert!(!exact(
+            col("day").eq(lit("2024-01-01")).and(col("id").eq(lit(1)))
+        ));
+        assert!(!exact(col("day").eq(col("region"))));
+        assert!(!exact(col("region").in_list(vec![lit("eu")], true)));
+        assert!(!exact(col("region").like(lit("e%"))));
+        assert!(!exact(
+            col("region").in_list((0..=MAX_IN_LIST as i64).map(lit).collect(), false)
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use std::{any::Any, error::Error, sync::Arc};
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::datatypes::{Schema, SchemaRef},
+    datasource::{TableProvider, TableType},
+    error::DataFusionError,
+    execution::context::SessionState,
+    logical_expr::Expr,
+    physical_plan::ExecutionPlan,
+};
+
+use super::columns::Columns;
+use crate::{
+    client::{rest::types, Table},
+    unity::error::UnityError,
+};
+
+/// A table registered in Unity Catalog that cannot be opened, e.g. because
+/// its storage is unreachable or its view definition no longer plans.
+///
+/// Only the metadata of the table is exposed, so the table can still be
+/// dropped. Reading it fails with the error that prevented opening it.
+pub(crate) struct UnavailableTable {
+    schema: SchemaRef,
+    table_type: TableType,
+    error: Arc<dyn Error + Send + Sync>,
+}
+
+impl UnavailableTable {
+    /// Describe `table`, which failed to open with `error`.
+    pub fn new(table: &Table, error: UnityError) -> Self {
+        Self::with_error(table, Arc::new(error))
+    }
+
+    /// Describe `table` after it has been dropped.
+    pub fn dropped(table: &Table) -> Self {
+        let error =
+            DataFusionError::Plan(format!("table '{}' has been dropped", table.full_name()));
+        Self::with_error(table, Arc::new(error))
+    }
+
+    fn with_error(table: &Table, error: Arc<dyn Error + Send + Sync>) -> Self {
+        // the columns are informational, since the table cannot be read
+        let schema = match Columns::try_new(&table.full_name(), table.columns()) {
+            Ok(columns) => columns.delta_schema(),
+            Err(_) => Schema::empty(),
+        };
+        let table_type = match table.table_type() {
+            Some(
+                types::TableType::View
+                | types::TableType::MaterializedView
+                | types::TableType::StreamingTable,
+            ) => TableType::View,
+            _ => TableType::Base,
+        };
+        Self {
+            schema: Arc::new(schema),
+            table_type,
+            error,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for UnavailableTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table_type
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        _projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        Err(DataFusionError::External(Box::new(self.error.clone())))
+    }
+}
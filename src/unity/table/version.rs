@@ -0,0 +1,70 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// A historical snapshot of a Delta table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TableVersion {
+    /// The snapshot at a version of the transaction log.
+    Version(i64),
+    /// The latest snapshot committed at or before a point in time.
+    Timestamp(DateTime<Utc>),
+}
+
+impl TableVersion {
+    /// Split the version off a table name of the form `name@v42` or
+    /// `name@yyyyMMddHHmmssSSS`, the syntax Databricks uses for time travel.
+    pub fn split(name: &str) -> Option<(&str, TableVersion)> {
+        let (name, version) = name.rsplit_once('@')?;
+        if name.is_empty() {
+            return None;
+        }
+        if let Some(version) = version.strip_prefix('v') {
+            return version
+                .parse()
+                .ok()
+                .map(|v| (name, TableVersion::Version(v)));
+        }
+        NaiveDateTime::parse_from_str(version, "%Y%m%d%H%M%S%3f")
+            .ok()
+            .map(|timestamp| (name, TableVersion::Timestamp(timestamp.and_utc())))
+    }
+}
+
+impl fmt::Display for TableVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(version) => write!(f, "version {}", version),
+            Self::Timestamp(timestamp) => write!(f, "timestamp {}", timestamp.to_rfc3339()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_split_version_suffix() {
+        assert_eq!(
+            TableVersion::split("sales@v42"),
+            Some(("sales", TableVersion::Version(42)))
+        );
+        assert_eq!(
+            TableVersion::split("sales@20240131120000123"),
+            Some((
+                "sales",
+                TableVersion::Timestamp(
+                    Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap()
+                        + chrono::Duration::milliseconds(123)
+                )
+            ))
+        );
+        assert_eq!(TableVersion::split("sales"), None);
+        assert_eq!(TableVersion::split("sales@vx"), None);
+        assert_eq!(TableVersion::split("sales@2024"), None);
+        assert_eq!(TableVersion::split("@v1"), None);
+    }
+}
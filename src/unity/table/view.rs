@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use datafusion::{
+    catalog::CatalogProviderList,
+    datasource::view::ViewTable,
+    execution::{context::SessionState, runtime_env::RuntimeEnv},
+    prelude::SessionConfig,
+};
+
+use crate::{client::Table, unity::error::UnityError};
+
+/// Plan the definition of a Unity Catalog view against `catalog_list`.
+///
+/// Unqualified table names in the definition resolve against the catalog and
+/// schema that contain the view, like they do in Unity Catalog.
+pub(crate) async fn view_table(
+    table: &Table,
+    catalog_list: Arc<dyn CatalogProviderList>,
+) -> Result<ViewTable, UnityError> {
+    let name = table.full_name();
+    let Some(definition) = table.view_definition() else {
+        return Err(UnityError::InvalidResponse {
+            name,
+            message: "view has no definition".to_string(),
+        });
+    };
+
+    let config = SessionConfig::new()
+        .with_create_default_catalog_and_schema(false)
+        .with_default_catalog_and_schema(table.catalog_name(), table.schema_name());
+    let state = SessionState::new_with_config_rt_and_catalog_list(
+        config,
+        Arc::new(RuntimeEnv::default()),
+        catalog_list,
+    );
+    let unsupported = |e: datafusion::error::DataFusionError| UnityError::Unsupported {
+        name: name.clone(),
+        message: format!("cannot plan view definition: {}", e),
+    };
+    let plan = state
+        .create_logical_plan(definition)
+        .await
+        .map_err(unsupported)?;
+
+    ViewTable::try_new(plan, Some(definition.to_string())).map_err(unsupported)
+}
@@ -0,0 +1,195 @@
+use std::{
+    any::Any,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::{compute::cast, datatypes::SchemaRef, error::ArrowError, record_batch::RecordBatch},
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{
+        insert::DataSink,
+        metrics::MetricsSet,
+        stream::RecordBatchStreamAdapter,
+        streaming::{PartitionStream, StreamingTableExec},
+        DisplayAs, DisplayFormatType,
+    },
+};
+use deltalake::{delta_datafusion::DataFusionMixins, protocol::SaveMode, DeltaOps};
+use futures::{future, stream, StreamExt, TryStreamExt};
+
+use super::{cache::DeltaTableCache, credentials::VendedCredentials};
+use crate::unity::error::UnityError;
+
+/// Appends to or overwrites a Unity Delta table, for `INSERT INTO` and
+/// `INSERT OVERWRITE`.
+///
+/// The table is opened with its own, uncached snapshot so that the commit is
+/// made with read-write credentials. Once committed, the cached snapshot is
+/// dropped so the next query sees the new version.
+pub(crate) struct DeltaSink {
+    name: String,
+    location: String,
+    tables: Arc<DeltaTableCache>,
+    credentials: Option<Arc<VendedCredentials>>,
+    mode: SaveMode,
+}
+
+impl DeltaSink {
+    pub fn new(
+        name: String,
+        location: String,
+        tables: Arc<DeltaTableCache>,
+        credentials: Option<Arc<VendedCredentials>>,
+        overwrite: bool,
+    ) -> Self {
+        Self {
+            name,
+            location,
+            tables,
+            credentials,
+            mode: if overwrite {
+                SaveMode::Overwrite
+            } else {
+                SaveMode::Append
+            },
+        }
+    }
+
+    async fn write(&self, data: SendableRecordBatchStream) -> Result<u64, UnityError> {
+        let delta = |e| UnityError::delta(&self.name, e);
+        let table = self
+            .tables
+            .open_for_write(&self.name, &self.location, self.credentials.clone())
+            .await?;
+        let schema = table
+            .snapshot()
+            .and_then(|snapshot| snapshot.input_schema())
+            .map_err(delta)?;
+
+        let rows = Arc::new(AtomicU64::new(0));
+        let mut data = {
+            let (schema, rows) = (schema.clone(), rows.clone());
+            data.try_filter(|batch| future::ready(batch.num_rows() > 0))
+                .and_then(move |batch| {
+                    rows.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+                    future::ready(conform(&batch, &schema).map_err(Into::into))
+                })
+                .boxed()
+                .peekable()
+        };
+        // An empty overwrite still commits, to remove the existing rows.
+        if self.mode == SaveMode::Append && Pin::new(&mut data).peek().await.is_none() {
+            return Ok(0);
+        }
+
+        let input = OnceStream::new(Box::pin(RecordBatchStreamAdapter::new(
+            schema.clone(),
+            data,
+        )));
+        let plan =
+            StreamingTableExec::try_new(schema, vec![Arc::new(input)], None, [], false, None)
+                .map_err(|e| delta(e.into()))?;
+        DeltaOps(table)
+            .write(vec![])
+            .with_input_execution_plan(Arc::new(plan))
+            .with_save_mode(self.mode)
+            .await
+            .map_err(delta)?;
+        self.tables.invalidate(&self.location);
+
+        Ok(rows.load(Ordering::Relaxed))
+    }
+}
+
+/// A partition that hands out a stream that was already started, so that
+/// `deltalake` can write the input of `INSERT` as it arrives.
+struct OnceStream {
+    schema: SchemaRef,
+    stream: Mutex<Option<SendableRecordBatchStream>>,
+}
+
+impl OnceStream {
+    fn new(stream: SendableRecordBatchStream) -> Self {
+        Self {
+            schema: stream.schema(),
+            stream: Mutex::new(Some(stream)),
+        }
+    }
+}
+
+impl PartitionStream for OnceStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        match self.stream.lock().unwrap().take() {
+            Some(stream) => stream,
+            None => {
+                let err = DataFusionError::Internal("the input was already written".to_string());
+                Box::pin(RecordBatchStreamAdapter::new(
+                    self.schema.clone(),
+                    stream::once(future::ready(Err(err))),
+                ))
+            }
+        }
+    }
+}
+
+/// Reorder and cast the columns of `batch`, which follow the schema the
+/// provider reports to DataFusion, to the schema of the Delta table.
+fn conform(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch, ArrowError> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let column = batch.column_by_name(field.name()).ok_or_else(|| {
+                ArrowError::SchemaError(format!("column '{}' is missing", field.name()))
+            })?;
+            cast(column, field.data_type())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+impl fmt::Debug for DeltaSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeltaSink")
+            .field("name", &self.name)
+            .field("location", &self.location)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DisplayAs for DeltaSink {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeltaSink(table={}, mode={:?})", self.name, self.mode)
+    }
+}
+
+#[async_trait]
+impl DataSink for DeltaSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        data: SendableRecordBatchStream,
+        _context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        Ok(self.write(data).await?)
+    }
+}